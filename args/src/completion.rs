@@ -0,0 +1,262 @@
+//======================= shell completion =======================
+use crate::{Parser,OptSpec,Positional,Hint,Arity};
+use std::str::FromStr;
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Shell {
+	Bash,
+	Zsh,
+	Fish,
+}
+//so it can be used with Args::get, e.g. `args.get::<Shell>("completion")`
+impl FromStr for Shell {
+	type Err = String;
+	fn from_str(shell: &str) -> Result<Self,Self::Err> {
+		match shell {
+			"bash" => Ok(Shell::Bash),
+			"zsh" => Ok(Shell::Zsh),
+			"fish" => Ok(Shell::Fish),
+			_ => Err(format!("unknown shell {shell:?}")),
+		}
+	}
+}
+impl Parser {
+	//a script that gives tab completion for the options, subcommands and positionals
+	// sl --completion bash > /usr/share/bash-completion/completions/sl
+	// sl --completion zsh > /usr/share/zsh/site-functions/_sl
+	// sl --completion fish > ~/.config/fish/completions/sl.fish
+	pub fn completion(&self, shell: Shell) -> String {
+		//argv[0] might be a path
+		let name = self.get_name().rsplit('/').next().unwrap_or_default().to_string();
+		match shell {
+			Shell::Bash => bash(self,&name),
+			Shell::Zsh => zsh(self,&name),
+			Shell::Fish => fish(self,&name),
+		}
+	}
+}
+//====== helpers ======
+//every parser along with the names of the subcommands leading to it, starting with the program
+fn walk<'a>(parser: &'a Parser, path: Vec<String>, output: &mut Vec<(Vec<String>,&'a Parser)>){
+	output.push( (path.clone(),parser) );
+	for sub in &parser.subcommands {
+		let mut sub_path = path.clone();
+		sub_path.push(sub.name.clone().unwrap_or_default());
+		walk(sub,sub_path,output);
+	}
+}
+fn identifier(name: &str) -> String {
+	name.chars().map(|c| if c.is_ascii_alphanumeric() {c} else {'_'}).collect()
+}
+fn option_names(spec: &OptSpec) -> Vec<String> {
+	let mut names = vec![];
+	if let Some(short) = &spec.short {names.push(format!("-{short}"))}
+	if let Some(long) = &spec.long {names.push(format!("--{long}"))}
+	names
+}
+fn subcommand_names(parser: &Parser) -> Vec<&str> {
+	parser.subcommands.iter().filter_map(|sub| sub.name.as_deref()).collect()
+}
+//the most useful hint out of all the positionals, since we do not track which one is being typed
+fn positional_hint(positionals: &[Positional]) -> Hint {
+	let hints = positionals.iter().map(Positional::get_hint).collect::<Vec<_>>();
+	if hints.contains(&Hint::File) {Hint::File}
+	else if hints.contains(&Hint::Directory) {Hint::Directory}
+	else {Hint::Anything}
+}
+fn single_quote(text: &str) -> String {
+	format!("'{}'",text.replace('\'',"'\\''"))
+}
+//====== bash ======
+fn bash(parser: &Parser, name: &str) -> String {
+	let compgen = |hint| match hint {
+		Hint::Anything => "()",
+		Hint::File => "($(compgen -f -- \"$cur\"))",
+		Hint::Directory => "($(compgen -d -- \"$cur\"))",
+	};
+	let mut parsers = vec![];
+	walk(parser,vec![identifier(name)],&mut parsers);
+	let function = format!("_{}",identifier(name));
+	let mut script = format!("{function}() {{\n");
+	script += "\tlocal cur=\"${COMP_WORDS[COMP_CWORD]}\" prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n";
+	script += &format!("\tlocal command=\"{}\" i\n",identifier(name));
+	//====== find which subcommand we are in ======
+	if parsers.len() > 1 {
+		script += "\tfor ((i=1; i<COMP_CWORD; i++)); do\n";
+		script += "\t\tcase \"$command:${COMP_WORDS[i]}\" in\n";
+		for (path,_) in &parsers[1..] {
+			let (sub_name,parent) = path.split_last().unwrap();
+			script += &format!("\t\t\t{}:{sub_name}) command=\"{}\";;\n",parent.join("/"),path.join("/"));
+		}
+		script += "\t\tesac\n";
+		script += "\tdone\n";
+	}
+	//====== complete for that subcommand ======
+	script += "\tcase \"$command\" in\n";
+	for (path,parser) in &parsers {
+		let specs = parser.specs();
+		script += &format!("\t\t{})\n",path.join("/"));
+		//the previous word was an option that takes a parameter
+		let parameter_specs = specs.iter().filter(|spec| spec.takes_parameter()).collect::<Vec<_>>();
+		if !parameter_specs.is_empty() {
+			script += "\t\t\tcase \"$prev\" in\n";
+			for spec in parameter_specs {
+				script += &format!("\t\t\t\t{}) COMPREPLY={}; return;;\n",option_names(spec).join("|"),compgen(spec.get_hint()));
+			}
+			script += "\t\t\tesac\n";
+		}
+		let options = specs.iter().flat_map(option_names).collect::<Vec<_>>().join(" ");
+		script += "\t\t\tif [[ \"$cur\" == -* ]]; then\n";
+		script += &format!("\t\t\t\tCOMPREPLY=($(compgen -W \"{options}\" -- \"$cur\"))\n");
+		script += "\t\t\telse\n";
+		let subcommands = subcommand_names(parser);
+		if subcommands.is_empty() {
+			script += &format!("\t\t\t\tCOMPREPLY={}\n",compgen(positional_hint(&parser.positionals)));
+		}else{
+			script += &format!("\t\t\t\tCOMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n",subcommands.join(" "));
+		}
+		script += "\t\t\tfi\n";
+		script += "\t\t\t;;\n";
+	}
+	script += "\tesac\n";
+	script += "}\n";
+	script += &format!("complete -F {function} {name}\n");
+	script
+}
+//====== zsh ======
+fn zsh(parser: &Parser, name: &str) -> String {
+	let mut parsers = vec![];
+	walk(parser,vec![identifier(name)],&mut parsers);
+	let mut script = format!("#compdef {name}\n");
+	for (path,parser) in &parsers {
+		let function = path.iter().map(|name| identifier(name)).collect::<Vec<_>>().join("_");
+		script += &format!("_{function}() {{\n");
+		let mut arguments = vec![];
+		//====== options ======
+		for spec in parser.specs() {
+			let help = spec.help.as_deref().unwrap_or("").replace('[',"\\[").replace(']',"\\]");
+			let mut argument = format!("[{help}]");
+			if let Some(placeholder) = spec.get_placeholder() {
+				argument += &format!(":{placeholder}:{}",zsh_action(spec.get_hint()));
+			}
+			let names = option_names(&spec);
+			arguments.push(match names.as_slice() {
+				[short,long] => format!("'({short} {long})'{{{short},{long}}}{}",single_quote(&argument)),
+				[name] => single_quote(&format!("{name}{argument}")),
+				_ => continue,
+			});
+		}
+		//====== positionals or subcommands ======
+		let subcommands = &parser.subcommands;
+		if subcommands.is_empty() {
+			for positional in &parser.positionals {
+				let action = zsh_action(positional.get_hint());
+				let name = &positional.name;
+				match positional.arity {
+					Arity::Exactly(n) => for _ in 0..n {arguments.push(single_quote(&format!(":{name}:{action}")))},
+					Arity::Optional => arguments.push(single_quote(&format!("::{name}:{action}"))),
+					Arity::ZeroOrMore | Arity::OneOrMore => arguments.push(single_quote(&format!("*:{name}:{action}"))),
+				}
+			}
+		}else{
+			arguments.push("'1:command:->command'".into());
+			arguments.push("'*::arg:->args'".into());
+		}
+		//====== put it together ======
+		if subcommands.is_empty() {
+			script += &format!("\t_arguments \\\n\t\t{}\n",arguments.join(" \\\n\t\t"));
+		}else{
+			script += "\tlocal context state state_descr line\n";
+			script += "\ttypeset -A opt_args\n";
+			script += &format!("\t_arguments -C \\\n\t\t{}\n",arguments.join(" \\\n\t\t"));
+			script += "\tcase $state in\n";
+			script += "\t\tcommand)\n";
+			script += "\t\t\tlocal -a commands\n";
+			script += "\t\t\tcommands=(\n";
+			for sub in subcommands {
+				let summary = sub.description.as_deref().and_then(|d| d.lines().next()).unwrap_or("");
+				let sub_name = sub.name.as_deref().unwrap_or("");
+				script += &format!("\t\t\t\t{}\n",single_quote(&format!("{sub_name}:{summary}")));
+			}
+			script += "\t\t\t)\n";
+			script += "\t\t\t_describe command commands\n";
+			script += "\t\t\t;;\n";
+			script += "\t\targs)\n";
+			script += "\t\t\tcase $line[1] in\n";
+			for sub_name in subcommand_names(parser) {
+				script += &format!("\t\t\t\t{sub_name}) _{function}_{} ;;\n",identifier(sub_name));
+			}
+			script += "\t\t\tesac\n";
+			script += "\t\t\t;;\n";
+			script += "\tesac\n";
+		}
+		script += "}\n";
+	}
+	script += &format!("_{} \"$@\"\n",identifier(name));
+	script
+}
+fn zsh_action(hint: Hint) -> &'static str {
+	match hint {
+		Hint::Anything => " ",
+		Hint::File => "_files",
+		Hint::Directory => "_files -/",
+	}
+}
+//====== fish ======
+fn fish(parser: &Parser, name: &str) -> String {
+	let quote = |text: &str| format!("'{}'",text.replace('\\',"\\\\").replace('\'',"\\'"));
+	let directories = "'(__fish_complete_directories (commandline -ct))'";
+	let mut parsers = vec![];
+	walk(parser,vec![],&mut parsers);
+	//no files unless a positional or option asks for them
+	let mut script = format!("complete -c {name} -f\n");
+	for (path,parser) in &parsers {
+		//====== only complete for this subcommand ======
+		let mut conditions = path.iter()
+			.map(|sub_name| format!("__fish_seen_subcommand_from {sub_name}"))
+			.collect::<Vec<_>>();
+		let subcommands = subcommand_names(parser);
+		if !subcommands.is_empty() {
+			//options and subcommand names stop once one of the subcommands has been typed
+			conditions.push(format!("not __fish_seen_subcommand_from {}",subcommands.join(" ")));
+		}
+		let command = |conditions: &[String]| match conditions.is_empty() {
+			true => format!("complete -c {name}"),
+			false => format!("complete -c {name} -n {}",quote(&conditions.join("; and "))),
+		};
+		//====== options ======
+		for spec in parser.specs() {
+			let mut line = command(&conditions);
+			if let Some(short) = &spec.short {line += &format!(" -s {short}")}
+			if let Some(long) = &spec.long {line += &format!(" -l {long}")}
+			if spec.takes_parameter() {
+				line += match spec.get_hint() {
+					Hint::Anything => " -x".to_string(),
+					Hint::File => " -r -F".to_string(),
+					Hint::Directory => format!(" -x -a {directories}"),
+				}.as_str();
+			}
+			if let Some(help) = &spec.help {line += &format!(" -d {}",quote(help))}
+			script += &line;
+			script += "\n";
+		}
+		//====== subcommands or positionals ======
+		if subcommands.is_empty() {
+			match positional_hint(&parser.positionals) {
+				Hint::Anything => (),
+				Hint::File => script += &format!("{} -F\n",command(&conditions)),
+				Hint::Directory => script += &format!("{} -a {directories}\n",command(&conditions)),
+			}
+		}else{
+			for sub in &parser.subcommands {
+				let mut line = format!("{} -a {}",command(&conditions),sub.name.as_deref().unwrap_or(""));
+				if let Some(summary) = sub.description.as_deref().and_then(|d| d.lines().next()) {
+					line += &format!(" -d {}",quote(summary));
+				}
+				script += &line;
+				script += "\n";
+			}
+		}
+	}
+	script
+}
@@ -1,15 +1,61 @@
 //======================= library =======================
+mod completion;
+pub use completion::Shell;
 #[derive(Debug,PartialEq)]
 pub struct Args {
 	//          arg  parameter
 	pub short: Vec<(String,Option<String>)>,
 	pub long: Vec<(String,Option<String>)>,
 	pub other: Vec<String>,
+	//the name of the subcommand that was used and its arguments
+	pub subcommand: Option<(String,Box<Args>)>,
+	specs: Vec<OptSpec>,
+	positionals: Vec<Positional>,
+	//every option given, in order
+	occurrences: Vec<Occurrence>,
+	//where each of other came from in argv
+	other_indices: Vec<usize>,
 }
 #[derive(Debug,Clone,PartialEq)]
+struct Occurrence {
+	name: String, //spec or positional name
+	value: Option<String>,
+	source: Source,
+	index: Option<usize>, //None if not from the command line
+}
+//the usizes are indexes into the arguments passed in, Parser::parse_env counts argv[0]
+#[derive(Debug,Clone,PartialEq)]
 pub enum ArgError {
-	UnknownArgument(ArgType),
-	MissingParameter(ArgType),
+	//the argument, its index and the closest known option e.g. "--depth"
+	UnknownArgument(ArgType,usize,Option<String>),
+	MissingParameter(ArgType,usize),
+	//"--flag=value" where flag does not take a parameter
+	UnexpectedParameter(ArgType,usize),
+	//the option, the value that failed to parse and the option's index if it came from the command line
+	InvalidValue(ArgType,String,Option<usize>),
+	MissingRequired(ArgType),
+	//given more than once with RepeatPolicy::Error, the index is of the second one
+	RepeatedOption(ArgType,usize),
+	//the name, its index and the closest known subcommand
+	UnknownSubcommand(String,usize,Option<String>),
+	//the name of the first positional argument that is missing
+	TooFewPositionals(String),
+	//the first value that was not expected
+	TooManyPositionals(String,usize),
+	//the option, the one it conflicts with that was given before it and its index
+	ConflictingOptions(ArgType,ArgType,Option<usize>),
+	//the option, the one it requires and its index
+	MissingDependency(ArgType,ArgType,Option<usize>),
+	//the first positional of a parser that also has subcommands, the parser is built wrong
+	PositionalsWithSubcommands(String),
+}
+//where an option's value came from, in order of precedence
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Source {
+	CommandLine,
+	Env,
+	Config,
+	Default,
 }
 #[derive(Debug,Clone,PartialEq)]
 pub enum ArgType {
@@ -17,55 +63,681 @@ pub enum ArgType {
 	Short(String),
 	Long(String),
 }
+//======================= option specs =======================
+//what kind of parameter an option takes, values are checked against this in Args::new
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum ValueType {
+	Flag, //no parameter
+	String,
+	Integer,
+	Float,
+	Bool,
+	Path,
+}
+impl ValueType {
+	pub fn is_valid(&self, value: &str) -> bool {
+		match self {
+			ValueType::Flag | ValueType::String | ValueType::Path => true,
+			ValueType::Integer => value.parse::<i128>().is_ok(),
+			ValueType::Float => value.parse::<f64>().is_ok(),
+			ValueType::Bool => value.parse::<bool>().is_ok(),
+		}
+	}
+}
+//what shell completion should offer for a value
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Hint {
+	Anything,
+	File,
+	Directory,
+}
+//what Args::get does when an option is given more than once
+//Args::get_all and Args::count see every occurrence regardless
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum RepeatPolicy {
+	LastWins,
+	FirstWins,
+	Error,
+}
+#[derive(Debug,Clone,PartialEq)]
+pub struct OptSpec {
+	name: String,
+	short: Option<String>,
+	long: Option<String>,
+	help: Option<String>,
+	value_type: ValueType,
+	default: Option<String>,
+	required: bool,
+	placeholder: Option<String>,
+	repeat: RepeatPolicy,
+	env: Option<String>,
+	config_key: Option<String>,
+	hint: Option<Hint>,
+	group: Option<String>,
+	conflicts: Vec<String>,
+	requires: Vec<String>,
+}
+impl OptSpec {
+	//the name is what you pass to Args::get, it does not have to match short or long
+	// OptSpec::new("depth").short("d").long("depth").value(ValueType::Integer).default("3")
+	pub fn new(name: &str) -> Self {
+		Self {
+			name: name.into(),
+			short: None,
+			long: None,
+			help: None,
+			value_type: ValueType::Flag,
+			default: None,
+			required: false,
+			placeholder: None,
+			repeat: RepeatPolicy::LastWins,
+			env: None,
+			config_key: None,
+			hint: None,
+			group: None,
+			conflicts: vec![],
+			requires: vec![],
+		}
+	}
+	pub fn short(mut self, short: &str) -> Self {
+		self.short = Some(short.into());
+		self
+	}
+	pub fn long(mut self, long: &str) -> Self {
+		self.long = Some(long.into());
+		self
+	}
+	pub fn help(mut self, help: &str) -> Self {
+		self.help = Some(help.into());
+		self
+	}
+	pub fn value(mut self, value_type: ValueType) -> Self {
+		self.value_type = value_type;
+		self
+	}
+	//setting a default on a flag makes it take a string parameter
+	pub fn default(mut self, default: impl ToString) -> Self {
+		if self.value_type == ValueType::Flag {self.value_type = ValueType::String}
+		self.default = Some(default.to_string());
+		self
+	}
+	pub fn required(mut self) -> Self {
+		self.required = true;
+		self
+	}
+	//environment variable to use if the option is not on the command line
+	//for flags, anything other than "", "0", "false", "no" or "off" counts as given
+	pub fn env(mut self, var: &str) -> Self {
+		self.env = Some(var.into());
+		self
+	}
+	//key in the config section given to Parser::config to use if the option
+	//is not on the command line or in the environment
+	pub fn config_key(mut self, key: &str) -> Self {
+		self.config_key = Some(key.into());
+		self
+	}
+	//for shell completion, ValueType::Path gives Hint::File by default
+	pub fn hint(mut self, hint: Hint) -> Self {
+		self.hint = Some(hint);
+		self
+	}
+	pub fn on_repeat(mut self, repeat: RepeatPolicy) -> Self {
+		self.repeat = repeat;
+		self
+	}
+	//options in the same group can not be given together, e.g. "--colour" and "--no-colour"
+	pub fn group(mut self, group: &str) -> Self {
+		self.group = Some(group.into());
+		self
+	}
+	//by name, it works both ways so only one of them needs it
+	pub fn conflicts_with(mut self, name: &str) -> Self {
+		self.conflicts.push(name.into());
+		self
+	}
+	//by name, if this option is given the other one has to be as well
+	pub fn requires(mut self, name: &str) -> Self {
+		self.requires.push(name.into());
+		self
+	}
+	//the name of the parameter in the help message, e.g. "n" for `--depth <n>`
+	pub fn placeholder(mut self, placeholder: &str) -> Self {
+		self.placeholder = Some(placeholder.into());
+		self
+	}
+	pub fn get_name(&self) -> &str {&self.name}
+	pub fn get_short(&self) -> Option<&str> {self.short.as_deref()}
+	pub fn get_long(&self) -> Option<&str> {self.long.as_deref()}
+	pub fn get_help(&self) -> Option<&str> {self.help.as_deref()}
+	pub fn get_value_type(&self) -> ValueType {self.value_type}
+	pub fn get_default(&self) -> Option<&str> {self.default.as_deref()}
+	pub fn is_required(&self) -> bool {self.required}
+	pub fn get_repeat_policy(&self) -> RepeatPolicy {self.repeat}
+	pub fn get_env(&self) -> Option<&str> {self.env.as_deref()}
+	pub fn get_config_key(&self) -> Option<&str> {self.config_key.as_deref()}
+	pub fn get_hint(&self) -> Hint {default_hint(self.hint,self.value_type)}
+	pub fn get_group(&self) -> Option<&str> {self.group.as_deref()}
+	pub fn get_conflicts(&self) -> &[String] {&self.conflicts}
+	pub fn get_requires(&self) -> &[String] {&self.requires}
+	pub fn conflicts(&self, other: &OptSpec) -> bool {
+		self.name != other.name && (
+			self.conflicts.contains(&other.name) || other.conflicts.contains(&self.name)
+			|| (self.group.is_some() && self.group == other.group)
+		)
+	}
+	pub fn get_placeholder(&self) -> Option<&str> {
+		match self.value_type {
+			ValueType::Flag => None,
+			_ if self.placeholder.is_some() => self.placeholder.as_deref(),
+			ValueType::String => Some("value"),
+			ValueType::Integer => Some("n"),
+			ValueType::Float => Some("number"),
+			ValueType::Bool => Some("bool"),
+			ValueType::Path => Some("path"),
+		}
+	}
+	pub fn takes_parameter(&self) -> bool {
+		self.value_type != ValueType::Flag
+	}
+	//how the option is refered to in errors, long is preferred
+	pub fn arg_type(&self) -> ArgType {
+		match (&self.long,&self.short) {
+			(Some(long),_) => ArgType::Long(long.clone()),
+			(None,Some(short)) => ArgType::Short(short.clone()),
+			(None,None) => ArgType::Other(self.name.clone()),
+		}
+	}
+	fn check_value(&self, value: &str, index: Option<usize>) -> Result<(),ArgError> {
+		if self.value_type.is_valid(value) {Ok(())}
+		else {Err(ArgError::InvalidValue(self.arg_type(),value.into(),index))}
+	}
+}
+//the old tuple format still works:
+//       short         long              parameter
+// (Some("h"),    Some("help"),     false    )
+impl From<(Option<&str>,Option<&str>,bool)> for OptSpec {
+	fn from((short,long,parameter): (Option<&str>,Option<&str>,bool)) -> Self {
+		let mut spec = Self::new(long.or(short).unwrap_or(""));
+		spec.short = short.map(String::from);
+		spec.long = long.map(String::from);
+		if parameter {spec.value_type = ValueType::String}
+		spec
+	}
+}
+//how a short option that takes a parameter gets it when it is grouped with others
+//Parser uses Attached unless told otherwise, Args::new uses Stacked
+#[derive(Debug,Clone,Copy,PartialEq,Default)]
+pub enum ShortParameters {
+	//like getopt, everything after the option is its parameter, so "-vd3" and "-vd 3" both give d "3"
+	//and "-ov" gives o "v"
+	#[default]
+	Attached,
+	//each option in the group that takes a parameter takes the next argument in turn,
+	//so "-pabc 1 2 3" gives p "1", a "2" and so on. Nothing is taken from the group itself
+	Stacked,
+}
+//======================= positional arguments =======================
+//how many values a positional argument takes
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Arity {
+	Exactly(usize),
+	Optional,
+	ZeroOrMore,
+	OneOrMore,
+}
+impl Arity {
+	fn min(&self) -> usize {
+		match self {
+			Arity::Exactly(n) => *n,
+			Arity::Optional | Arity::ZeroOrMore => 0,
+			Arity::OneOrMore => 1,
+		}
+	}
+	fn max(&self) -> usize {
+		match self {
+			Arity::Exactly(n) => *n,
+			Arity::Optional => 1,
+			Arity::ZeroOrMore | Arity::OneOrMore => usize::MAX,
+		}
+	}
+}
+// Positional::new("file").help("the file to rename").value(ValueType::Path)
+#[derive(Debug,Clone,PartialEq)]
+pub struct Positional {
+	name: String,
+	help: Option<String>,
+	value_type: ValueType,
+	arity: Arity,
+	hint: Option<Hint>,
+}
+impl Positional {
+	//takes exactly one value unless arity is called
+	pub fn new(name: &str) -> Self {
+		Self {
+			name: name.into(),
+			help: None,
+			value_type: ValueType::String,
+			arity: Arity::Exactly(1),
+			hint: None,
+		}
+	}
+	pub fn help(mut self, help: &str) -> Self {
+		self.help = Some(help.into());
+		self
+	}
+	pub fn value(mut self, value_type: ValueType) -> Self {
+		self.value_type = value_type;
+		self
+	}
+	pub fn arity(mut self, arity: Arity) -> Self {
+		self.arity = arity;
+		self
+	}
+	//for shell completion, ValueType::Path gives Hint::File by default
+	pub fn hint(mut self, hint: Hint) -> Self {
+		self.hint = Some(hint);
+		self
+	}
+	pub fn get_name(&self) -> &str {&self.name}
+	pub fn get_help(&self) -> Option<&str> {self.help.as_deref()}
+	pub fn get_value_type(&self) -> ValueType {self.value_type}
+	pub fn get_arity(&self) -> Arity {self.arity}
+	pub fn get_hint(&self) -> Hint {default_hint(self.hint,self.value_type)}
+	//how it looks in the usage line, e.g. "<file>", "[path]" or "[path] ..."
+	pub fn usage(&self) -> String {
+		let name = &self.name;
+		match self.arity {
+			Arity::Exactly(1) => format!("<{name}>"),
+			Arity::Exactly(n) => vec![format!("<{name}>");n].join(" "),
+			Arity::Optional => format!("[{name}]"),
+			Arity::ZeroOrMore => format!("[{name}] ..."),
+			Arity::OneOrMore => format!("<{name}> ..."),
+		}
+	}
+}
+fn default_hint(hint: Option<Hint>, value_type: ValueType) -> Hint {
+	match (hint,value_type) {
+		(Some(hint),_) => hint,
+		(None,ValueType::Path) => Hint::File,
+		(None,_) => Hint::Anything,
+	}
+}
+//======================= parser =======================
+//holds the option specs along with everything needed to print help
+// let parser = Parser::new()
+//	.name("sl")
+//	.description("list directories as a tree")
+//	.usage("[options] [path] ...")
+//	.opt(OptSpec::new("depth").short("d").long("depth").value(ValueType::Integer).help("the maximum depth"));
+// let args = parser.parse(env::args().skip(1).collect())?;
+// if args.is_present("help") {print!("{}",parser.help())}
+#[derive(Debug,Clone,PartialEq)]
+pub struct Parser {
+	name: Option<String>,
+	description: Option<String>,
+	usage: Option<String>,
+	epilog: Option<String>,
+	specs: Vec<OptSpec>,
+	positionals: Vec<Positional>,
+	subcommands: Vec<Parser>,
+	auto_help: bool,
+	config: Option<ConfigSection>,
+	short_parameters: ShortParameters,
+}
+impl Default for Parser {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+impl Parser {
+	//a -h, --help option is added unless one is already given or no_auto_help is called
+	pub fn new() -> Self {
+		Self {
+			name: None,
+			description: None,
+			usage: None,
+			epilog: None,
+			specs: vec![],
+			positionals: vec![],
+			subcommands: vec![],
+			auto_help: true,
+			config: None,
+			short_parameters: ShortParameters::Attached,
+		}
+	}
+	pub fn name(mut self, name: &str) -> Self {
+		self.name = Some(name.into());
+		self
+	}
+	pub fn description(mut self, description: &str) -> Self {
+		self.description = Some(description.into());
+		self
+	}
+	//what goes after the program name in the usage line, defaults to "[options]"
+	pub fn usage(mut self, usage: &str) -> Self {
+		self.usage = Some(usage.into());
+		self
+	}
+	//printed after the options
+	pub fn epilog(mut self, epilog: &str) -> Self {
+		self.epilog = Some(epilog.into());
+		self
+	}
+	pub fn opt(mut self, spec: impl Into<OptSpec>) -> Self {
+		self.specs.push(spec.into());
+		self
+	}
+	pub fn opts<F: Into<OptSpec>>(mut self, specs: Vec<F>) -> Self {
+		self.specs.extend(specs.into_iter().map(Into::into));
+		self
+	}
+	//positional arguments are filled in the order they are declared
+	//if any are declared, the number of non option arguments is checked against them
+	//a parser can have positionals or subcommands but not both, give them to the subcommands instead
+	pub fn positional(mut self, positional: Positional) -> Self {
+		self.positionals.push(positional);
+		self
+	}
+	//where OptSpec::config_key looks for values
+	pub fn config(mut self, section: ConfigSection) -> Self {
+		self.config = Some(section);
+		self
+	}
+	//the subcommand's name is what has to be typed to use it
+	// Parser::new().subcommand(Parser::new().name("install").description("install a package"))
+	pub fn subcommand(mut self, subcommand: Parser) -> Self {
+		self.subcommands.push(subcommand);
+		self
+	}
+	pub fn get_subcommand(&self, name: &str) -> Option<&Parser> {
+		self.subcommands.iter().find(|sub| sub.name.as_deref() == Some(name))
+	}
+	pub fn no_auto_help(mut self) -> Self {
+		self.auto_help = false;
+		self
+	}
+	//see ShortParameters, the default is Attached
+	pub fn short_parameters(mut self, short_parameters: ShortParameters) -> Self {
+		self.short_parameters = short_parameters;
+		self
+	}
+	pub fn get_name(&self) -> String {
+		match &self.name {
+			Some(name) => name.clone(),
+			None => std::env::args().next().unwrap_or("program".into()),
+		}
+	}
+	//the specs including the automatic help option
+	pub fn specs(&self) -> Vec<OptSpec> {
+		let mut specs = self.specs.clone();
+		let has_help = specs.iter().any(|spec|
+			spec.name == "help" || spec.long.as_deref() == Some("help") || spec.short.as_deref() == Some("h")
+		);
+		if self.auto_help && !has_help {
+			specs.insert(0,OptSpec::new("help").short("h").long("help").help("print help"));
+		}
+		specs
+	}
+	//argv[0] should not be included
+	//if there are subcommands, the first non option argument has to be one of them
+	//and everything after it is parsed by that subcommand
+	pub fn parse(&self, args: Vec<String>) -> Result<Args,ArgError> {
+		self.parse_from(args,0)
+	}
+	//parses std::env::args(), skipping argv[0]
+	pub fn parse_env(&self) -> Result<Args,ArgError> {
+		self.parse_from(std::env::args().skip(1).collect(),1)
+	}
+	//offset is the index of args[0] in the original arguments, for errors
+	fn parse_from(&self, args: Vec<String>, offset: usize) -> Result<Args,ArgError> {
+		//the first non option argument is always the subcommand, so positionals would never be filled
+		if let (Some(positional),false) = (self.positionals.first(),self.subcommands.is_empty()) {
+			return Err(ArgError::PositionalsWithSubcommands(positional.name.clone()));
+		}
+		let (mut parsed,rest) = Args::parse_options(args,self.specs(),!self.subcommands.is_empty(),offset,self.short_parameters)?;
+		//asking for help skips the checks for missing arguments so `--help` always works,
+		//and the fallbacks so a bad value in the environment or config can not get in the way
+		let help = parsed.is_present("help");
+		if !help {parsed.apply_fallbacks(self.config.as_ref())}
+		parsed.validate(!help)?;
+		if !help && !self.positionals.is_empty() {parsed.assign_positionals(self.positionals.clone())?}
+		let mut rest = rest.into_iter();
+		if let Some((index,name)) = rest.next() {
+			let Some(subcommand) = self.get_subcommand(&name) else {
+				let names = self.subcommands.iter().filter_map(|sub| sub.name.as_deref());
+				let suggestion = closest_match(&name,names).map(String::from);
+				return Err(ArgError::UnknownSubcommand(name,index,suggestion));
+			};
+			let sub_args = subcommand.parse_from(rest.map(|(_,arg)| arg).collect(),index+1)?;
+			parsed.subcommand = Some((name,Box::new(sub_args)));
+		}
+		Ok(parsed)
+	}
+	//====== help ======
+	pub fn usage_line(&self) -> String {
+		self.usage_line_for(&self.get_name())
+	}
+	fn usage_line_for(&self, name: &str) -> String {
+		if let Some(usage) = &self.usage {return format!("usage: {name} {usage}")}
+		let mut usage = format!("usage: {name} [options]");
+		for positional in &self.positionals {
+			usage += &format!(" {}",positional.usage());
+		}
+		if !self.subcommands.is_empty() {usage += " <command> ..."}
+		usage
+	}
+	pub fn help(&self) -> String {
+		self.help_for_name(&self.get_name())
+	}
+	//help for the deepest subcommand used in args, e.g. "usage: lever install [options]"
+	pub fn help_for(&self, args: &Args) -> String {
+		let mut name = self.get_name();
+		let mut parser = self;
+		let mut args = args;
+		while let Some((sub_name,sub_args)) = &args.subcommand {
+			let Some(subcommand) = parser.get_subcommand(sub_name) else {break};
+			name += &format!(" {sub_name}");
+			parser = subcommand;
+			args = sub_args;
+		}
+		parser.help_for_name(&name)
+	}
+	fn help_for_name(&self, name: &str) -> String {
+		let mut help = self.usage_line_for(name) + "\n";
+		if let Some(description) = &self.description {
+			help += &format!("{description}\n");
+		}
+		if self.positionals.iter().any(|positional| positional.help.is_some()) {
+			help += "arguments:\n";
+			help += &format_columns(self.positionals.iter()
+				.map(|positional| (positional.usage(),positional.help.clone().unwrap_or_default()))
+				.collect());
+		}
+		let specs = self.specs();
+		if !specs.is_empty() {
+			help += "options:\n";
+			help += &format_options(&specs);
+		}
+		if !self.subcommands.is_empty() {
+			help += "commands:\n";
+			help += &format_commands(&self.subcommands);
+		}
+		if let Some(epilog) = &self.epilog {
+			help += &format!("{epilog}\n");
+		}
+		help
+	}
+}
+//the first line of each subcommand's description
+//	install : install a package
+//	update  : update installed packages
+pub fn format_commands(subcommands: &[Parser]) -> String {
+	format_columns(subcommands.iter().map(|sub|{
+		let summary = sub.description.as_deref().and_then(|d| d.lines().next()).unwrap_or("");
+		(sub.name.clone().unwrap_or_default(),summary.to_string())
+	}).collect())
+}
+//lines up the option descriptions like this:
+//	-h, --help      : print help
+//	    --width <n> : the width (default: 80)
+pub fn format_options(specs: &[OptSpec]) -> String {
+	format_columns(specs.iter().map(|spec|{
+		let mut column = match (&spec.short,&spec.long) {
+			(Some(short),Some(long)) => format!("-{short}, --{long}"),
+			(Some(short),None) => format!("-{short}"),
+			(None,Some(long)) => format!("    --{long}"),
+			(None,None) => spec.name.clone(),
+		};
+		if let Some(placeholder) = spec.get_placeholder() {
+			column += &format!(" <{placeholder}>");
+		}
+		let mut description = spec.help.clone().unwrap_or_default();
+		if spec.required && spec.default.is_none() {description += " (required)"}
+		if let Some(var) = &spec.env {description += &format!(" (env: {var})")}
+		if let Some(default) = &spec.default {description += &format!(" (default: {default})")}
+		(column,description)
+	}).collect())
+}
+fn format_columns(rows: Vec<(String,String)>) -> String {
+	let width = rows.iter().map(|(column,_)| column.chars().count()).max().unwrap_or(0);
+	let mut result = String::new();
+	for (column,description) in rows {
+		result += format!("\t{column:<width$} : {}",description.trim()).trim_end();
+		result += "\n";
+	}
+	result
+}
+use iniconfig::{ConfigSection,edit_distance};
 use std::fmt::{Display,Formatter};
+impl ArgError {
+	//where in the arguments the error is, if it is anywhere
+	pub fn index(&self) -> Option<usize> {
+		match self {
+			ArgError::UnknownArgument(_,index,_)
+			| ArgError::MissingParameter(_,index)
+			| ArgError::UnexpectedParameter(_,index)
+			| ArgError::RepeatedOption(_,index)
+			| ArgError::UnknownSubcommand(_,index,_)
+			| ArgError::TooManyPositionals(_,index) => Some(*index),
+			ArgError::InvalidValue(_,_,index)
+			| ArgError::ConflictingOptions(_,_,index)
+			| ArgError::MissingDependency(_,_,index) => *index,
+			ArgError::MissingRequired(_)
+			| ArgError::TooFewPositionals(_)
+			| ArgError::PositionalsWithSubcommands(_) => None,
+		}
+	}
+}
+//e.g. "unknown option --colur (argument 2), did you mean --colour?"
 impl Display for ArgError {
 	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error>{
-		let result = format!("{self:?}");
-		let _ = f.write_str(&result);
-		Ok(())
+		match self {
+			ArgError::UnknownArgument(arg,_,_) => write!(f,"unknown option {arg}")?,
+			ArgError::MissingParameter(arg,_) => write!(f,"missing parameter for {arg}")?,
+			ArgError::UnexpectedParameter(arg,_) => write!(f,"{arg} does not take a parameter")?,
+			ArgError::InvalidValue(arg,value,_) => write!(f,"invalid value {value:?} for {arg}")?,
+			ArgError::MissingRequired(arg) => write!(f,"missing required option {arg}")?,
+			ArgError::RepeatedOption(arg,_) => write!(f,"{arg} can only be given once")?,
+			ArgError::UnknownSubcommand(name,_,_) => write!(f,"unknown command {name:?}")?,
+			ArgError::TooFewPositionals(name) => write!(f,"missing argument <{name}>")?,
+			ArgError::TooManyPositionals(value,_) => write!(f,"unexpected argument {value:?}")?,
+			ArgError::ConflictingOptions(arg,other,_) => write!(f,"{arg} can not be used with {other}")?,
+			ArgError::MissingDependency(arg,other,_) => write!(f,"{arg} requires {other}")?,
+			ArgError::PositionalsWithSubcommands(name) => write!(f,"argument <{name}> can not be used with subcommands")?,
+		}
+		if let Some(index) = self.index() {write!(f," (argument {index})")?}
+		match self {
+			ArgError::UnknownArgument(_,_,Some(suggestion)) => write!(f,", did you mean {suggestion}?"),
+			ArgError::UnknownSubcommand(_,_,Some(suggestion)) => write!(f,", did you mean {suggestion:?}?"),
+			_ => Ok(()),
+		}
 	}
 }
 impl std::error::Error for ArgError {}
+//"-d", "--depth" or just the name for positionals
+impl Display for ArgType {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error>{
+		match self {
+			ArgType::Short(short) => write!(f,"-{short}"),
+			ArgType::Long(long) => write!(f,"--{long}"),
+			ArgType::Other(other) => write!(f,"{other}"),
+		}
+	}
+}
+//the candidate with the smallest edit distance, if it is close enough to be a typo
+fn closest_match<'a>(typed: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+	let max_distance = (typed.chars().count()/3).max(1);
+	candidates
+		.map(|candidate| (edit_distance(typed,candidate),candidate))
+		.filter(|(distance,_)| *distance <= max_distance)
+		.min_by_key(|(distance,_)| *distance)
+		.map(|(_,candidate)| candidate)
+}
 impl Args {
-	//format is passed a tuple like this:
+	//format is a list of OptSpecs, or tuples like this:
 	//       short         long              parameter
 	// vec![(Some("h"),    Some("help"),     false    ),
 	//      (None,         Some("width"),    true     )]
 	//passing a parameter looks like this:
 	// `--width 20` or `-w 20`
-	pub fn new(args: Vec<String>, format: Vec<(Option<&str>,Option<&str>,bool)>) -> Result<Self,ArgError> {
-		let arg_has_parameter = move |arg: &ArgType|{
-			for arg_info in &format{
-				match arg {
-					ArgType::Short(ref arg) => {
-						if arg_info.0 == Some(&arg) {return Some(arg_info.2)}
-					},
-					ArgType::Long(ref arg) => {
-						if arg_info.1 == Some(&arg) {return Some(arg_info.2)}
-					},
-					ArgType::Other(_) => {
-						return Some(false) //other argumens never have a parameter
-					},
-				}
+	//grouped short options are ShortParameters::Stacked as they always have been
+	pub fn new<F: Into<OptSpec>>(args: Vec<String>, format: Vec<F>) -> Result<Self,ArgError> {
+		Self::new_with(args,format,ShortParameters::Stacked)
+	}
+	//new, but with a choice of how grouped short options get their parameters
+	//ShortParameters::Attached allows `-w20` and `-vw20` like Parser does
+	pub fn new_with<F: Into<OptSpec>>(args: Vec<String>, format: Vec<F>, short_parameters: ShortParameters) -> Result<Self,ArgError> {
+		let specs = format.into_iter().map(Into::into).collect::<Vec<OptSpec>>();
+		let (mut args,_) = Self::parse_options(args,specs,false,0,short_parameters)?;
+		args.apply_fallbacks(None);
+		args.validate(true)?;
+		Ok(args)
+	}
+	//if stop_at_other is set, returns as soon as a non option argument is reached
+	//with it and everything after it, along with their indexes
+	fn parse_options(args: Vec<String>, specs: Vec<OptSpec>, stop_at_other: bool, offset: usize, short_parameters: ShortParameters) -> Result<(Self,Vec<(usize,String)>),ArgError> {
+		let find_spec = |arg: &ArgType|{
+			specs.iter().find(|spec| match arg {
+				ArgType::Short(arg) => spec.short.as_deref() == Some(arg),
+				ArgType::Long(arg) => spec.long.as_deref() == Some(arg),
+				ArgType::Other(_) => false,
+			})
+		};
+		let arg_has_parameter = |arg: &ArgType|{
+			match arg {
+				ArgType::Other(_) => Some(false), //other argumens never have a parameter
+				_ => find_spec(arg).map(OptSpec::takes_parameter),
 			}
-			None
 		};
-		let confirm_is_arg = |arg: Option<String>,param|{
-			if arg.is_none() {Err(ArgError::MissingParameter(param))}
-			else {Ok(arg)}
+		let unknown_argument = |arg: ArgType,index|{
+			let suggestion = match &arg {
+				ArgType::Long(long) => closest_match(long,specs.iter().filter_map(|spec| spec.long.as_deref()))
+					.map(|long| format!("--{long}")),
+				_ => None,
+			};
+			ArgError::UnknownArgument(arg,index,suggestion)
+		};
+		let confirm_is_arg = |arg: Option<(usize,String)>,param,index|{
+			match arg {
+				Some((_,arg)) => Ok(Some(arg)),
+				None => Err(ArgError::MissingParameter(param,index)),
+			}
 		};
 		//====== initialise ======
-		let mut args_struct = Args {short: vec![], long: vec![], other: vec![]};
-		let mut arg_iter = args.into_iter();
-		loop {
-			//====== next arg ======
-			let arg = match arg_iter.next(){
-				Some(a) => a,
-				None => break,
-			};
+		let mut args_struct = Args {
+			short: vec![], long: vec![], other: vec![], subcommand: None,
+			specs: vec![], positionals: vec![], occurrences: vec![], other_indices: vec![],
+		};
+		let mut arg_iter = args.into_iter().enumerate().map(|(i,arg)| (i+offset,arg));
+		//====== for arg in args ======
+		while let Some((index,arg)) = arg_iter.next() {
 			//====== anything past "--" is treated as an other arg ======
 			if arg == "--"{
-				args_struct.other.extend(&mut arg_iter);
+				for (index,arg) in &mut arg_iter {
+					args_struct.other.push(arg);
+					args_struct.other_indices.push(index);
+				}
 				break;
 			};
 			//====== classify the argument type ======
@@ -73,35 +745,167 @@ impl Args {
 				//====== short ======
 				ArgType::Short(arg) => {
 					//split up all the args
-					for ch in arg.chars(){
-						if arg_has_parameter(&ArgType::Short(ch.into())).ok_or(ArgError::UnknownArgument(ArgType::Short(ch.into())))?{
-							args_struct.short.push(
-								(ch.to_string(),confirm_is_arg(arg_iter.next(),ArgType::Short(arg.clone()))?)
-							);
-						}else{
-							args_struct.short.push( (ch.to_string(),None) );
+					for (i,ch) in arg.char_indices(){
+						let short = ArgType::Short(ch.into());
+						if !arg_has_parameter(&short).ok_or_else(|| unknown_argument(short.clone(),index))? {
+							args_struct.push_short(&specs,ch.to_string(),None,index);
+							continue;
+						}
+						//"-d3" and "-vd3" give d the parameter "3"
+						let rest = &arg[i+ch.len_utf8()..];
+						if short_parameters == ShortParameters::Attached && !rest.is_empty() {
+							args_struct.push_short(&specs,ch.to_string(),Some(rest.into()),index);
+							break;
 						}
+						let parameter = confirm_is_arg(arg_iter.next(),short,index)?;
+						args_struct.push_short(&specs,ch.to_string(),parameter,index);
 					}
-					()
 				},
 				//====== long ======
 				ArgType::Long(arg) => {
+					//"--depth=3" is the same as "--depth 3"
+					let (arg,inline_parameter) = match arg.split_once('=') {
+						Some((arg,parameter)) => (arg.to_string(),Some(parameter.to_string())),
+						None => (arg,None),
+					};
 					if arg_has_parameter(&ArgType::Long(arg.clone()))
-						.ok_or(ArgError::UnknownArgument(ArgType::Long(arg.clone())))?{
-						args_struct.long.push(
-							(arg.to_string(),confirm_is_arg(arg_iter.next(),ArgType::Long(arg.clone()))?)
-						);
+						.ok_or_else(|| unknown_argument(ArgType::Long(arg.clone()),index))?{
+						let parameter = match inline_parameter {
+							Some(parameter) => Some(parameter),
+							None => confirm_is_arg(arg_iter.next(),ArgType::Long(arg.clone()),index)?,
+						};
+						args_struct.push_long(&specs,arg,parameter,index);
+					}else if inline_parameter.is_some(){
+						return Err(ArgError::UnexpectedParameter(ArgType::Long(arg),index));
 					}else{
-						args_struct.long.push( (arg,None) )
+						args_struct.push_long(&specs,arg,None,index);
 					}
 				},
 				//====== other ======
+				ArgType::Other(arg) if stop_at_other => {
+					let rest = std::iter::once((index,arg)).chain(arg_iter).collect();
+					args_struct.specs = specs;
+					return Ok((args_struct,rest));
+				},
 				ArgType::Other(arg) => {
-					args_struct.other.push(arg.clone());
+					args_struct.other.push(arg);
+					args_struct.other_indices.push(index);
 				},
 			};
 		};
-		Ok(args_struct)
+		args_struct.specs = specs;
+		Ok((args_struct,vec![]))
+	}
+	fn push_short(&mut self, specs: &[OptSpec], short: String, parameter: Option<String>, index: usize){
+		if let Some(spec) = specs.iter().find(|spec| spec.short.as_ref() == Some(&short)) {
+			self.occurrences.push(Occurrence {
+				name: spec.name.clone(), value: parameter.clone(), source: Source::CommandLine, index: Some(index),
+			});
+		}
+		self.short.push( (short,parameter) );
+	}
+	fn push_long(&mut self, specs: &[OptSpec], long: String, parameter: Option<String>, index: usize){
+		if let Some(spec) = specs.iter().find(|spec| spec.long.as_ref() == Some(&long)) {
+			self.occurrences.push(Occurrence {
+				name: spec.name.clone(), value: parameter.clone(), source: Source::CommandLine, index: Some(index),
+			});
+		}
+		self.long.push( (long,parameter) );
+	}
+	//fills in options missing from the command line from the environment, then the config
+	fn apply_fallbacks(&mut self, config: Option<&ConfigSection>){
+		for spec in &self.specs {
+			if self.count(&spec.name) > 0 {continue}
+			//the command line wins over a conflicting fallback
+			if self.specs.iter().any(|other| spec.conflicts(other) && self.is_present(&other.name)) {continue}
+			let env_value = spec.env.as_ref()
+				.and_then(|var| std::env::var(var).ok())
+				.map(|value| (value,Source::Env));
+			let config_value = || spec.config_key.as_ref()
+				.and_then(|key| config?.properties().get(key).cloned())
+				.map(|value| (value,Source::Config));
+			let Some((value,source)) = env_value.or_else(config_value) else {continue};
+			let value = if spec.takes_parameter() {Some(value)}
+				else if !matches!(value.trim().to_lowercase().as_str(),"" | "0" | "false" | "no" | "off") {None}
+				else {continue};
+			self.occurrences.push(Occurrence {name: spec.name.clone(), value, source, index: None});
+		}
+	}
+	//====== validate against the specs ======
+	//check_required covers required options and options required by others
+	fn validate(&self, check_required: bool) -> Result<(),ArgError> {
+		for spec in &self.specs {
+			//a default is always there to fall back on
+			if check_required && spec.required && spec.default.is_none() && !self.is_present(&spec.name) {
+				return Err(ArgError::MissingRequired(spec.arg_type()));
+			}
+			let occurrences = self.occurrences.iter()
+				.filter(|occurrence| occurrence.name == spec.name)
+				.collect::<Vec<_>>();
+			if spec.repeat == RepeatPolicy::Error && occurrences.len() > 1 {
+				return Err(ArgError::RepeatedOption(spec.arg_type(),occurrences[1].index.unwrap_or(0)));
+			}
+			for occurrence in occurrences {
+				if let Some(value) = &occurrence.value {spec.check_value(value,occurrence.index)?}
+			}
+		}
+		//====== conflicts and dependencies ======
+		//position of the first occurrence, command line options come before fallbacks
+		let first = |name: &str| self.occurrences.iter().position(|occurrence| occurrence.name == name);
+		for spec in &self.specs {
+			let Some(position) = first(&spec.name) else {continue};
+			let index = self.occurrences[position].index;
+			for other in &self.specs {
+				if !spec.conflicts(other) {continue}
+				//only report the one that came second
+				if matches!(first(&other.name),Some(other_position) if other_position < position) {
+					return Err(ArgError::ConflictingOptions(spec.arg_type(),other.arg_type(),index));
+				}
+			}
+			if !check_required {continue}
+			for name in &spec.requires {
+				if self.is_present(name) {continue}
+				let other = match self.spec(name) {
+					Some(other) => other.arg_type(),
+					None => ArgType::Other(name.clone()),
+				};
+				return Err(ArgError::MissingDependency(spec.arg_type(),other,index));
+			}
+		}
+		Ok(())
+	}
+	//splits self.other between the positionals, each takes as many as it can
+	//while leaving enough for the ones after it
+	fn assign_positionals(&mut self, positionals: Vec<Positional>) -> Result<(),ArgError> {
+		//====== check there are enough to go round ======
+		let mut needed = 0;
+		for positional in &positionals {
+			needed += positional.arity.min();
+			if needed > self.other.len() {
+				return Err(ArgError::TooFewPositionals(positional.name.clone()));
+			}
+		}
+		//====== hand them out ======
+		let mut values = self.other.iter().zip(self.other_indices.iter().copied());
+		let mut remaining = self.other.len();
+		for (i,positional) in positionals.iter().enumerate() {
+			let needed_after = positionals[i+1..].iter().map(|p| p.arity.min()).sum::<usize>();
+			let count = (remaining - needed_after).min(positional.arity.max());
+			for (value,index) in values.by_ref().take(count) {
+				if !positional.value_type.is_valid(value) {
+					return Err(ArgError::InvalidValue(ArgType::Other(positional.name.clone()),value.clone(),Some(index)));
+				}
+				self.occurrences.push(Occurrence {
+					name: positional.name.clone(), value: Some(value.clone()), source: Source::CommandLine, index: Some(index),
+				});
+			}
+			remaining -= count;
+		}
+		if let Some((extra,index)) = values.next() {
+			return Err(ArgError::TooManyPositionals(extra.clone(),index));
+		}
+		self.positionals = positionals;
+		Ok(())
 	}
 	//returns relevant argtype with leading '-'s stripped off
 	pub fn classify(arg: String) -> ArgType {
@@ -109,11 +913,14 @@ impl Args {
 		// "--" is dealt with in new
 		// "-asdf" and "-b" are shorts
 		// "--hello" is a long
+		// "--hello=world" is a long, the "=world" is split off in new
 		// "900" is an other
-		if arg.len() == 0 || arg.len() == 1 {ArgType::Other(arg)}
-		else if arg[..2] == *"--" {ArgType::Long(arg[2..].into())}
-		else if arg[..1] == *"-" {ArgType::Short(arg[1..].into())}
-		else {ArgType::Other(arg)}
+		if arg == "-" {return ArgType::Other(arg)}
+		if let Some(long) = arg.strip_prefix("--") {return ArgType::Long(long.into())}
+		match arg.strip_prefix('-') {
+			Some(short) => ArgType::Short(short.into()),
+			None => ArgType::Other(arg),
+		}
 	}
 	pub fn has_long(&self, long: &str) -> bool {
 		for arg in &self.long {
@@ -128,7 +935,7 @@ impl Args {
 		false
 	}
 	pub fn has(&self, short: &str, long: &str) -> bool{
-		return self.has_long(long) || self.has_short(short)
+		self.has_long(long) || self.has_short(short)
 	}
 	pub fn get_arg<'a>(&'a self, short_opt: Option<&str>, long_opt: Option<&str>) -> Option<&'a str>{
 		if let Some(long) = long_opt {
@@ -143,6 +950,73 @@ impl Args {
 		}
 		None
 	}
+	//====== typed access by OptSpec or Positional name ======
+	pub fn spec(&self, name: &str) -> Option<&OptSpec> {
+		self.specs.iter().find(|spec| spec.name == name)
+	}
+	//true if the option or positional was given on the command line or filled in from the environment
+	//or config, defaults do not count. `source` tells them apart
+	pub fn is_present(&self, name: &str) -> bool {
+		self.count(name) > 0
+	}
+	//the raw parameter from the command line, falling back to the default
+	//which one is used if it was given more than once depends on the spec's RepeatPolicy
+	pub fn get_str(&self, name: &str) -> Option<&str> {
+		match self.get_occurrence(name) {
+			Some(occurrence) => occurrence.value.as_deref(),
+			None => self.spec(name)?.default.as_deref(),
+		}
+	}
+	//the occurrence get_str uses
+	fn get_occurrence(&self, name: &str) -> Option<&Occurrence> {
+		let mut occurrences = self.occurrences.iter()
+			.filter(|occurrence| occurrence.name == name && occurrence.value.is_some());
+		//positionals with more than one value give the first
+		match self.spec(name).map(|spec| spec.repeat) {
+			None | Some(RepeatPolicy::FirstWins) => occurrences.next(),
+			Some(RepeatPolicy::LastWins | RepeatPolicy::Error) => occurrences.next_back(),
+		}
+	}
+	//every parameter given to the option in order, e.g. ["a","b"] for `-I a --include b`
+	pub fn get_all(&self, name: &str) -> Vec<&str> {
+		self.occurrences.iter()
+			.filter(|occurrence| occurrence.name == name)
+			.filter_map(|occurrence| occurrence.value.as_deref())
+			.collect()
+	}
+	//how many times the option was given, e.g. 3 for `-vvv`
+	pub fn count(&self, name: &str) -> usize {
+		self.occurrences.iter()
+			.filter(|occurrence| occurrence.name == name)
+			.count()
+	}
+	//where the value get would return came from, None if there is no value at all
+	pub fn source(&self, name: &str) -> Option<Source> {
+		match self.occurrences.iter().find(|occurrence| occurrence.name == name) {
+			Some(occurrence) => Some(occurrence.source),
+			None => self.spec(name)?.default.as_ref().map(|_| Source::Default),
+		}
+	}
+	//e.g. `args.get::<usize>("depth")?.unwrap_or(usize::MAX)`
+	//Ok(None) if the option was not given and has no default
+	pub fn get<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>,ArgError> {
+		let Some(value) = self.get_str(name) else {return Ok(None)};
+		match value.parse::<T>() {
+			Ok(value) => Ok(Some(value)),
+			Err(_) => {
+				let arg_type = match self.spec(name) {
+					Some(spec) => spec.arg_type(),
+					None => ArgType::Other(name.into()), //positional
+				};
+				let index = self.get_occurrence(name).and_then(|occurrence| occurrence.index);
+				Err(ArgError::InvalidValue(arg_type,value.into(),index))
+			},
+		}
+	}
+	//the name and arguments of the subcommand that was used
+	pub fn subcommand(&self) -> Option<(&str,&Args)> {
+		self.subcommand.as_ref().map(|(name,args)| (name.as_str(),args.as_ref()))
+	}
 }
 //======================= tests =======================
 #[cfg(test)]
@@ -182,7 +1056,7 @@ mod tests {
 			"--help","localhost", "8765", "-p", "999", "what", "--thing"
 		].into_iter().map(|x| x.to_string()).collect();
 		let args = Args::new(sample_args,format);
-		assert_eq!(args,Err(UnknownArgument(Long("thing".to_string()))));
+		assert_eq!(args,Err(UnknownArgument(Long("thing".to_string()),6,None)));
 		/*assert_eq!(args.other,vec!["localhost","8765","what"]);
 		assert_eq!(args.short,vec![(String::from("p"),Some(String::from("999")))]);
 		assert_eq!(args.long,vec![(String::from("help"),None)]);*/
@@ -200,9 +1074,544 @@ mod tests {
 			"--help","localhost", "8765", "-p", "999", "what", "--opt"
 		].into_iter().map(|x| x.to_string()).collect();
 		let args = Args::new(sample_args,format);
-		assert_eq!(args,Err(MissingParameter(Long("opt".to_string()))));
+		assert_eq!(args,Err(MissingParameter(Long("opt".to_string()),6)));
 	}
+	#[test]
 	fn missing_short_parameter_test(){
-		todo!();
+		use crate::ArgError::MissingParameter;
+		use crate::ArgType::Short;
+		let format =  vec![
+			(Some("p"),None,true),
+			(Some("h"),Some("help"),false),
+		];
+		let sample_args = vec![
+			"--help","localhost", "-hp"
+		].into_iter().map(|x| x.to_string()).collect();
+		let args = Args::new(sample_args,format);
+		assert_eq!(args,Err(MissingParameter(Short("p".to_string()),2)));
+	}
+	fn to_args(args: &[&str]) -> Vec<String> {
+		args.iter().map(|x| x.to_string()).collect()
+	}
+	#[test]
+	fn opt_spec_typed_values_test(){
+		let format = vec![
+			OptSpec::new("depth").short("d").long("depth").value(ValueType::Integer),
+			OptSpec::new("width").long("width").value(ValueType::Integer).default(80),
+			OptSpec::new("colour").short("n").long("no-colour"),
+		];
+		let args = Args::new(to_args(&["-d","3","file"]),format).unwrap();
+		assert_eq!(args.get::<usize>("depth"),Ok(Some(3)));
+		assert_eq!(args.get::<u32>("width"),Ok(Some(80)));
+		assert_eq!(args.get::<String>("missing"),Ok(None));
+		assert!(args.is_present("depth"));
+		assert!(!args.is_present("width"));
+		assert!(!args.is_present("colour"));
+		assert_eq!(args.other,vec!["file"]);
+	}
+	#[test]
+	fn typed_value_out_of_range_test(){
+		//parses as an integer but not as a u8
+		let format = vec![OptSpec::new("depth").short("d").value(ValueType::Integer)];
+		let args = Args::new(to_args(&["-d","-1"]),format).unwrap();
+		assert_eq!(args.get::<u8>("depth"),Err(ArgError::InvalidValue(ArgType::Short("d".into()),"-1".into(),Some(0))));
+	}
+	#[test]
+	fn opt_spec_validation_test(){
+		let format = vec![
+			OptSpec::new("depth").long("depth").value(ValueType::Integer),
+			OptSpec::new("output").short("o").value(ValueType::Path).required(),
+		];
+		assert_eq!(
+			Args::new(to_args(&["--depth","three","-o","a.out"]),format.clone()),
+			Err(ArgError::InvalidValue(ArgType::Long("depth".into()),"three".into(),Some(0)))
+		);
+		assert_eq!(
+			Args::new(to_args(&["--depth","3"]),format),
+			Err(ArgError::MissingRequired(ArgType::Short("o".into())))
+		);
+	}
+	#[test]
+	fn required_default_test(){
+		//a default satisfies required
+		let format = vec![OptSpec::new("output").short("o").value(ValueType::Path).required().default("a.out")];
+		let args = Args::new(vec![],format).unwrap();
+		assert_eq!(args.get_str("output"),Some("a.out"));
+		assert_eq!(args.source("output"),Some(Source::Default));
+	}
+	#[test]
+	fn help_test(){
+		let parser = Parser::new()
+			.name("sl")
+			.usage("[options] [path] ...")
+			.description("list directories as a tree")
+			.opt(OptSpec::new("no-colour").short("n").long("no-colour").help("turn off colour"))
+			.opt(OptSpec::new("depth").short("d").long("depth").value(ValueType::Integer).help("the maximum depth").default(3))
+			.opt(OptSpec::new("width").long("width").value(ValueType::Integer).placeholder("columns"))
+			.opt(OptSpec::new("output").short("o").value(ValueType::Path).required().help("output file"))
+			.epilog("have fun");
+		assert_eq!(parser.help(),[
+			"usage: sl [options] [path] ...",
+			"list directories as a tree",
+			"options:",
+			"\t-h, --help            : print help",
+			"\t-n, --no-colour       : turn off colour",
+			"\t-d, --depth <n>       : the maximum depth (default: 3)",
+			"\t    --width <columns> :",
+			"\t-o <path>             : output file (required)",
+			"have fun",
+			"",
+		].join("\n"));
+		assert!(parser.parse(to_args(&["-h","-o","x"])).unwrap().is_present("help"));
+	}
+	#[test]
+	fn help_skips_required_test(){
+		let parser = Parser::new().opt(OptSpec::new("output").short("o").value(ValueType::Path).required());
+		assert!(parser.parse(to_args(&["--help"])).is_ok());
+		assert_eq!(parser.parse(vec![]),Err(ArgError::MissingRequired(ArgType::Short("o".into()))));
+	}
+	#[test]
+	fn no_duplicate_help_test(){
+		let parser = Parser::new().opt((Some("h"),Some("help"),false)).no_auto_help();
+		assert_eq!(parser.specs().len(),1);
+		assert_eq!(Parser::new().opt((Some("h"),Some("help"),false)).specs().len(),1);
+	}
+	#[test]
+	fn subcommand_test(){
+		let parser = Parser::new()
+			.opt(OptSpec::new("verbose").short("v"))
+			.subcommand(Parser::new().name("install").opt(OptSpec::new("force").short("f")));
+		//options before the subcommand belong to the parent
+		let args = parser.parse(to_args(&["-v","install","-f","thing"])).unwrap();
+		assert!(args.is_present("verbose"));
+		let (name,sub_args) = args.subcommand().unwrap();
+		assert_eq!(name,"install");
+		assert!(sub_args.is_present("force"));
+		assert_eq!(sub_args.other,vec!["thing"]);
+		assert_eq!(parser.parse(to_args(&["-v"])).unwrap().subcommand(),None);
+	}
+	#[test]
+	fn subcommand_unknown_test(){
+		let parser = Parser::new()
+			.subcommand(Parser::new().name("install").opt(OptSpec::new("force").short("f")));
+		//the parent does not know about the subcommand's options
+		assert_eq!(
+			parser.parse(to_args(&["-f","install"])),
+			Err(ArgError::UnknownArgument(ArgType::Short("f".into()),0,None))
+		);
+		assert_eq!(
+			parser.parse(to_args(&["compile"])),
+			Err(ArgError::UnknownSubcommand("compile".into(),0,None))
+		);
+	}
+	#[test]
+	fn subcommand_help_test(){
+		let parser = Parser::new()
+			.name("lever")
+			.opt(OptSpec::new("verbose").short("v"))
+			.subcommand(Parser::new()
+				.name("install")
+				.description("install a package\nmore text")
+				.usage("[options] <package>")
+				.opt(OptSpec::new("force").short("f").long("force").help("reinstall")))
+			.subcommand(Parser::new().name("update").description("update packages"));
+		assert_eq!(parser.help(),[
+			"usage: lever [options] <command> ...",
+			"options:",
+			"\t-h, --help : print help",
+			"\t-v         :",
+			"commands:",
+			"\tinstall : install a package",
+			"\tupdate  : update packages",
+			"",
+		].join("\n"));
+		let args = parser.parse(to_args(&["install","--help"])).unwrap();
+		assert!(parser.help_for(&args).starts_with("usage: lever install [options] <package>\ninstall a package\n"));
+	}
+	#[test]
+	fn subcommand_positional_test(){
+		let parser = Parser::new().subcommand(Parser::new().name("install")).positional(Positional::new("package"));
+		let err = parser.parse(to_args(&["install"])).unwrap_err();
+		assert_eq!(err,ArgError::PositionalsWithSubcommands("package".into()));
+		assert_eq!(err.index(),None);
+		let parser = Parser::new().subcommand(Parser::new().name("install").positional(Positional::new("package")).subcommand(Parser::new().name("now")));
+		assert_eq!(parser.parse(to_args(&["install","now"])),Err(ArgError::PositionalsWithSubcommands("package".into())));
+	}
+	#[test]
+	fn attached_values_test(){
+		let format = vec![
+			(Some("d"),Some("depth"),true),
+			(Some("v"),Some("verbose"),false),
+			(Some("q"),None,false),
+			(Some("o"),Some("output"),true),
+		];
+		let sample_args = to_args(&["--depth=3","-d4","-vqo","out","-vo-","--output=a=b","--output=","x"]);
+		let args = Args::new_with(sample_args,format,ShortParameters::Attached).unwrap();
+		assert_eq!(args.long,vec![
+			("depth",Some("3")),
+			("output",Some("a=b")),
+			("output",Some("")),
+		].into_iter().map(|x| (x.0.to_string(),x.1.map(|s| s.to_string())) ).collect::<Vec<(String,Option<String>)>>());
+		assert_eq!(args.short,vec![
+			("d",Some("4")),
+			("v",None),
+			("q",None),
+			("o",Some("out")),
+			("v",None),
+			("o",Some("-")),
+		].into_iter().map(|x| (x.0.to_string(),x.1.map(|s| s.to_string())) ).collect::<Vec<(String,Option<String>)>>());
+		assert_eq!(args.other,vec!["x"]);
+	}
+	#[test]
+	fn attached_short_takes_rest_test(){
+		let format = vec![
+			(Some("d"),Some("depth"),true),
+			(Some("v"),Some("verbose"),false),
+			(Some("q"),None,false),
+			(Some("o"),Some("output"),true),
+		];
+		//everything after an option that takes a parameter is its parameter, even other options
+		let args = Args::new_with(to_args(&["-ov","-dvq","x"]),format.clone(),ShortParameters::Attached).unwrap();
+		assert_eq!(args.get_str("output"),Some("v"));
+		assert_eq!(args.get_str("depth"),Some("vq"));
+		assert!(!args.is_present("verbose"));
+		assert_eq!(args.other,vec!["x"]);
+		assert_eq!(Args::new_with(to_args(&["-vqd"]),format,ShortParameters::Attached),Err(ArgError::MissingParameter(ArgType::Short("d".into()),0)));
+	}
+	#[test]
+	fn stacked_short_parameters_test(){
+		//stacked takes each parameter from the next argument instead
+		let parser = Parser::new().short_parameters(ShortParameters::Stacked)
+			.opt(OptSpec::new("depth").short("d").value(ValueType::Integer))
+			.opt(OptSpec::new("output").short("o").value(ValueType::Path));
+		let args = parser.parse(to_args(&["-do","3","a.out"])).unwrap();
+		assert_eq!(args.get::<u32>("depth"),Ok(Some(3)));
+		assert_eq!(args.get_str("output"),Some("a.out"));
+		assert_eq!(parser.parse(to_args(&["-d4"])),Err(ArgError::MissingParameter(ArgType::Short("d".into()),0)));
+	}
+	#[test]
+	fn attached_value_errors_test(){
+		let format = vec![(Some("v"),Some("verbose"),false)];
+		//flags can not be given a parameter
+		assert_eq!(
+			Args::new(to_args(&["--verbose=yes"]),format.clone()),
+			Err(ArgError::UnexpectedParameter(ArgType::Long("verbose".into()),0))
+		);
+		//the option name is still checked when a parameter is attached
+		assert_eq!(
+			Args::new(to_args(&["--width=3"]),format),
+			Err(ArgError::UnknownArgument(ArgType::Long("width".into()),0,None))
+		);
+	}
+	#[test]
+	fn repeated_options_test(){
+		let format = vec![
+			OptSpec::new("verbose").short("v").long("verbose"),
+			OptSpec::new("include").short("I").long("include").value(ValueType::Path),
+			OptSpec::new("level").short("l").value(ValueType::Integer),
+			OptSpec::new("first").short("f").value(ValueType::String).on_repeat(RepeatPolicy::FirstWins),
+			OptSpec::new("output").short("o").value(ValueType::Path),
+		];
+		let sample_args = to_args(&["-vvv","-I","a","--verbose","--include=b","-Ic","-l1","-l","2","-fx","-fy"]);
+		let args = Args::new_with(sample_args,format,ShortParameters::Attached).unwrap();
+		assert_eq!(args.count("verbose"),4);
+		assert_eq!(args.count("output"),0);
+		assert_eq!(args.get_all("include"),vec!["a","b","c"]);
+		assert_eq!(args.get::<u8>("level"),Ok(Some(2)));
+		assert_eq!(args.get_str("first"),Some("x"));
+	}
+	#[test]
+	fn repeated_values_checked_test(){
+		//every value is checked, not just the one get returns
+		let format = vec![OptSpec::new("level").short("l").value(ValueType::Integer)];
+		assert_eq!(
+			Args::new(to_args(&["-l","x","-l","1"]),format),
+			Err(ArgError::InvalidValue(ArgType::Short("l".into()),"x".into(),Some(0)))
+		);
+	}
+	#[test]
+	fn repeated_error_test(){
+		let format = vec![OptSpec::new("output").short("o").value(ValueType::Path).on_repeat(RepeatPolicy::Error)];
+		assert_eq!(
+			Args::new(to_args(&["-o","a","-o","b"]),format),
+			Err(ArgError::RepeatedOption(ArgType::Short("o".into()),2))
+		);
+	}
+	#[test]
+	fn positional_test(){
+		let parser = Parser::new()
+			.positional(Positional::new("file"))
+			.positional(Positional::new("match"))
+			.positional(Positional::new("replacement"));
+		let args = parser.parse(to_args(&["a.txt","a","b"])).unwrap();
+		assert_eq!(args.get_str("file"),Some("a.txt"));
+		assert_eq!(args.get_str("replacement"),Some("b"));
+		assert_eq!(parser.parse(to_args(&["a.txt","a"])),Err(ArgError::TooFewPositionals("replacement".into())));
+		assert_eq!(parser.parse(to_args(&["a","b","c","d"])),Err(ArgError::TooManyPositionals("d".into(),3)));
+		assert!(parser.parse(to_args(&["--help"])).unwrap().is_present("help"));
+	}
+	#[test]
+	fn positional_help_test(){
+		let parser = Parser::new()
+			.name("rename")
+			.positional(Positional::new("file").help("the file to rename"))
+			.positional(Positional::new("match"))
+			.positional(Positional::new("replacement"));
+		assert_eq!(parser.usage_line(),"usage: rename [options] <file> <match> <replacement>");
+		assert!(parser.help().contains(&[
+			"arguments:",
+			"\t<file>        : the file to rename",
+			"\t<match>       :",
+			"\t<replacement> :",
+			"options:",
+		].join("\n")));
+		let parser = Parser::new()
+			.name("cp")
+			.positional(Positional::new("source").arity(Arity::OneOrMore))
+			.positional(Positional::new("dest"))
+			.positional(Positional::new("mode").arity(Arity::Optional));
+		assert_eq!(parser.usage_line(),"usage: cp [options] <source> ... <dest> [mode]");
+	}
+	#[test]
+	fn positional_arity_test(){
+		let parser = Parser::new()
+			.positional(Positional::new("source").arity(Arity::OneOrMore))
+			.positional(Positional::new("dest"))
+			.positional(Positional::new("mode").arity(Arity::Optional).value(ValueType::Integer));
+		let args = parser.parse(to_args(&["a","b","c"])).unwrap();
+		assert_eq!(args.get_all("source"),vec!["a","b"]);
+		assert_eq!(args.get_str("dest"),Some("c"));
+		assert!(!args.is_present("mode"));
+		assert_eq!(args.get::<u32>("mode"),Ok(None));
+		let args = parser.parse(to_args(&["a","b"])).unwrap();
+		assert_eq!(args.get_all("source"),vec!["a"]);
+		assert_eq!(parser.parse(to_args(&["a"])),Err(ArgError::TooFewPositionals("dest".into())));
+	}
+	#[test]
+	fn positional_value_test(){
+		let parser = Parser::new()
+			.positional(Positional::new("count").value(ValueType::Integer));
+		assert_eq!(parser.parse(to_args(&["x"])),Err(ArgError::InvalidValue(ArgType::Other("count".into()),"x".into(),Some(0))));
+		//the values are still in other
+		assert_eq!(parser.parse(to_args(&["3"])).unwrap().other,vec!["3"]);
+	}
+	#[test]
+	fn non_ascii_test(){
+		let parser = Parser::new()
+			.opt(OptSpec::new("name").short("n").value(ValueType::String))
+			.positional(Positional::new("file"));
+		let args = parser.parse(to_args(&["ü.txt"])).unwrap();
+		assert_eq!(args.get_str("file"),Some("ü.txt"));
+		let args = parser.parse(to_args(&["-nö","é"])).unwrap();
+		assert_eq!(args.get_str("name"),Some("ö"));
+		assert_eq!(args.get_str("file"),Some("é"));
+		assert_eq!(Args::classify("ü".into()),ArgType::Other("ü".into()));
+		assert_eq!(Args::classify("-ü".into()),ArgType::Short("ü".into()));
+		assert_eq!(Args::classify("-".into()),ArgType::Other("-".into()));
+	}
+	//tests run in parallel, so every test that sets an environment variable uses its own
+	#[test]
+	fn fallback_order_test(){
+		let config = iniconfig::ConfigFile::from("[sl]\ndepth=2\nwidth=40\ncolour=no\n")
+			.into_iter().next().unwrap();
+		let parser = Parser::new()
+			.opt(OptSpec::new("depth").short("d").value(ValueType::Integer).env("ARGS_TEST_ORDER_DEPTH").config_key("depth"))
+			.opt(OptSpec::new("width").short("w").value(ValueType::Integer).config_key("width"))
+			.opt(OptSpec::new("height").value(ValueType::Integer).config_key("height").default(10))
+			.opt(OptSpec::new("quiet").short("q").env("ARGS_TEST_ORDER_QUIET"))
+			.opt(OptSpec::new("colour").config_key("colour"))
+			.config(config);
+		std::env::set_var("ARGS_TEST_ORDER_DEPTH","5");
+		std::env::set_var("ARGS_TEST_ORDER_QUIET","1");
+		//argv > env > config > default
+		let args = parser.parse(to_args(&["-w","3"])).unwrap();
+		assert_eq!(args.get::<u32>("width"),Ok(Some(3)));
+		assert_eq!(args.source("width"),Some(Source::CommandLine));
+		assert_eq!(args.get::<u32>("depth"),Ok(Some(5)));
+		assert_eq!(args.source("depth"),Some(Source::Env));
+		assert_eq!(args.get::<u32>("height"),Ok(Some(10)));
+		assert_eq!(args.source("height"),Some(Source::Default));
+		assert!(args.is_present("quiet"));
+		assert!(!args.is_present("colour"));
+		//fallbacks never show up as command line arguments
+		assert_eq!(args.short,vec![(String::from("w"),Some(String::from("3")))]);
+		std::env::remove_var("ARGS_TEST_ORDER_DEPTH");
+		std::env::remove_var("ARGS_TEST_ORDER_QUIET");
+	}
+	#[test]
+	fn fallback_config_test(){
+		let config = iniconfig::ConfigFile::from("[sl]\ndepth=2\n").into_iter().next().unwrap();
+		let parser = Parser::new()
+			.opt(OptSpec::new("depth").short("d").value(ValueType::Integer).env("ARGS_TEST_CONFIG_DEPTH").config_key("depth"))
+			.config(config);
+		let args = parser.parse(vec![]).unwrap();
+		assert_eq!(args.get::<u32>("depth"),Ok(Some(2)));
+		assert_eq!(args.source("depth"),Some(Source::Config));
+	}
+	#[test]
+	fn fallback_invalid_env_test(){
+		let parser = Parser::new()
+			.opt(OptSpec::new("width").short("w").value(ValueType::Integer).env("ARGS_TEST_INVALID_WIDTH"));
+		//values from the environment are checked too
+		std::env::set_var("ARGS_TEST_INVALID_WIDTH","wide");
+		assert_eq!(parser.parse(vec![]),Err(ArgError::InvalidValue(ArgType::Short("w".into()),"wide".into(),None)));
+		//but not when asking for help
+		assert!(parser.parse(to_args(&["--help"])).unwrap().is_present("help"));
+		std::env::remove_var("ARGS_TEST_INVALID_WIDTH");
+	}
+	#[test]
+	fn unknown_option_display_test(){
+		let parser = Parser::new()
+			.opt(OptSpec::new("colour").long("colour").value(ValueType::String))
+			.opt(OptSpec::new("depth").short("d").long("depth").value(ValueType::Integer));
+		let error = parser.parse(to_args(&["--depth","1","--colur=red"])).unwrap_err();
+		assert_eq!(error,ArgError::UnknownArgument(ArgType::Long("colur".into()),2,Some("--colour".into())));
+		assert_eq!(error.to_string(),"unknown option --colur (argument 2), did you mean --colour?");
+		//transposed letters are one edit
+		let error = parser.parse(to_args(&["--dpeth","1"])).unwrap_err();
+		assert_eq!(error.to_string(),"unknown option --dpeth (argument 0), did you mean --depth?");
+		//nothing close enough
+		let error = parser.parse(to_args(&["--verbose"])).unwrap_err();
+		assert_eq!(error.to_string(),"unknown option --verbose (argument 0)");
+	}
+	#[test]
+	fn subcommand_error_display_test(){
+		let parser = Parser::new()
+			.opt(OptSpec::new("depth").short("d").long("depth").value(ValueType::Integer))
+			.subcommand(Parser::new().name("install").positional(Positional::new("package")))
+			.subcommand(Parser::new().name("update"));
+		let error = parser.parse(to_args(&["-d","1","instal","x"])).unwrap_err();
+		assert_eq!(error.to_string(),"unknown command \"instal\" (argument 2), did you mean \"install\"?");
+		//indexes carry on into subcommands
+		let error = parser.parse(to_args(&["-d","1","install","a","b"])).unwrap_err();
+		assert_eq!(error,ArgError::TooManyPositionals("b".into(),4));
+		assert_eq!(error.to_string(),"unexpected argument \"b\" (argument 4)");
+	}
+	#[test]
+	fn value_error_display_test(){
+		let parser = Parser::new()
+			.opt(OptSpec::new("depth").short("d").long("depth").value(ValueType::Integer));
+		let error = parser.parse(to_args(&["-d","x"])).unwrap_err();
+		assert_eq!(error.to_string(),"invalid value \"x\" for --depth (argument 0)");
+		assert_eq!(parser.parse(to_args(&["--depth"])).unwrap_err().to_string(),"missing parameter for --depth (argument 0)");
+		assert_eq!(ArgError::MissingRequired(ArgType::Short("o".into())).to_string(),"missing required option -o");
+		assert_eq!(ArgError::TooFewPositionals("file".into()).to_string(),"missing argument <file>");
+	}
+	#[test]
+	fn bash_completion_test(){
+		let parser = Parser::new()
+			.name("/usr/bin/lever")
+			.opt(OptSpec::new("config").short("c").long("config").value(ValueType::Path).help("config file"))
+			.subcommand(Parser::new().name("install").description("install a package"))
+			.subcommand(Parser::new().name("track"));
+		let bash = parser.completion(Shell::Bash);
+		assert!(bash.starts_with("_lever() {\n"));
+		assert!(bash.contains("\t\t\tlever:install) command=\"lever/install\";;\n"));
+		assert!(bash.contains("\t\t\t\t-c|--config) COMPREPLY=($(compgen -f -- \"$cur\")); return;;\n"));
+		assert!(bash.contains("\t\t\t\tCOMPREPLY=($(compgen -W \"-h --help -c --config\" -- \"$cur\"))\n"));
+		assert!(bash.contains("\t\t\t\tCOMPREPLY=($(compgen -W \"install track\" -- \"$cur\"))\n"));
+		assert!(bash.ends_with("complete -F _lever lever\n"));
+	}
+	#[test]
+	fn zsh_completion_test(){
+		let parser = Parser::new()
+			.name("/usr/bin/lever")
+			.opt(OptSpec::new("config").short("c").long("config").value(ValueType::Path).help("config file"))
+			.subcommand(Parser::new().name("install").description("install a package")
+				.opt(OptSpec::new("prefix").long("prefix").value(ValueType::String).hint(Hint::Directory).help("it's [optional]"))
+				.positional(Positional::new("package")))
+			.subcommand(Parser::new().name("track")
+				.positional(Positional::new("path").hint(Hint::Directory).arity(Arity::OneOrMore)));
+		let zsh = parser.completion(Shell::Zsh);
+		assert!(zsh.starts_with("#compdef lever\n_lever() {\n"));
+		assert!(zsh.contains("\t\t'(-c --config)'{-c,--config}'[config file]:path:_files' \\\n"));
+		assert!(zsh.contains("\t\t\t\t'install:install a package'\n"));
+		assert!(zsh.contains("\t\t\t\tinstall) _lever_install ;;\n"));
+		assert!(zsh.contains("\t\t'--prefix[it'\\''s \\[optional\\]]:value:_files -/' \\\n\t\t':package: '\n"));
+		assert!(zsh.contains("\t\t'*:path:_files -/'\n"));
+		assert!(zsh.ends_with("_lever \"$@\"\n"));
+	}
+	#[test]
+	fn fish_completion_test(){
+		let parser = Parser::new()
+			.name("/usr/bin/lever")
+			.opt(OptSpec::new("config").short("c").long("config").value(ValueType::Path).help("config file"))
+			.subcommand(Parser::new().name("install").description("install a package")
+				.opt(OptSpec::new("prefix").long("prefix").value(ValueType::String).hint(Hint::Directory).help("it's [optional]"))
+				.positional(Positional::new("package")))
+			.subcommand(Parser::new().name("track")
+				.positional(Positional::new("path").hint(Hint::Directory).arity(Arity::OneOrMore)));
+		let fish = parser.completion(Shell::Fish);
+		assert_eq!(fish.lines().collect::<Vec<_>>(),vec![
+			"complete -c lever -f",
+			"complete -c lever -n 'not __fish_seen_subcommand_from install track' -s h -l help -d 'print help'",
+			"complete -c lever -n 'not __fish_seen_subcommand_from install track' -s c -l config -r -F -d 'config file'",
+			"complete -c lever -n 'not __fish_seen_subcommand_from install track' -a install -d 'install a package'",
+			"complete -c lever -n 'not __fish_seen_subcommand_from install track' -a track",
+			"complete -c lever -n '__fish_seen_subcommand_from install' -s h -l help -d 'print help'",
+			"complete -c lever -n '__fish_seen_subcommand_from install' -l prefix -x -a '(__fish_complete_directories (commandline -ct))' -d 'it\\'s [optional]'",
+			"complete -c lever -n '__fish_seen_subcommand_from track' -s h -l help -d 'print help'",
+			"complete -c lever -n '__fish_seen_subcommand_from track' -a '(__fish_complete_directories (commandline -ct))'",
+		]);
+	}
+	#[test]
+	fn shell_from_str_test(){
+		assert_eq!("zsh".parse::<Shell>(),Ok(Shell::Zsh));
+		assert!("tcsh".parse::<Shell>().is_err());
+	}
+	#[test]
+	fn group_conflicts_test(){
+		let format = vec![
+			OptSpec::new("colour").long("colour").value(ValueType::String).group("colour"),
+			OptSpec::new("no-colour").short("n").long("no-colour").group("colour"),
+			OptSpec::new("quiet").short("q"),
+		];
+		assert!(Args::new(to_args(&["--colour","always","-q"]),format.clone()).is_ok());
+		let error = Args::new(to_args(&["--colour","always","-n"]),format.clone()).unwrap_err();
+		assert_eq!(error,ArgError::ConflictingOptions(ArgType::Long("no-colour".into()),ArgType::Long("colour".into()),Some(2)));
+		assert_eq!(error.to_string(),"--no-colour can not be used with --colour (argument 2)");
+		//giving the same one twice is fine
+		assert!(Args::new(to_args(&["-nn"]),format).is_ok());
+	}
+	#[test]
+	fn conflicts_both_ways_test(){
+		let format = vec![
+			OptSpec::new("quiet").short("q").conflicts_with("verbose"),
+			OptSpec::new("verbose").short("v"),
+		];
+		let error = Args::new(to_args(&["-v","-q"]),format.clone()).unwrap_err();
+		assert_eq!(error,ArgError::ConflictingOptions(ArgType::Short("q".into()),ArgType::Short("v".into()),Some(1)));
+		let error = Args::new(to_args(&["-qv"]),format).unwrap_err();
+		assert_eq!(error,ArgError::ConflictingOptions(ArgType::Short("v".into()),ArgType::Short("q".into()),Some(0)));
+	}
+	#[test]
+	fn dependencies_test(){
+		let format = vec![
+			OptSpec::new("verbose").short("v"),
+			OptSpec::new("output").short("o").value(ValueType::Path).requires("format"),
+			OptSpec::new("format").short("f").value(ValueType::String),
+		];
+		let error = Args::new(to_args(&["-v","-o","out.txt"]),format.clone()).unwrap_err();
+		assert_eq!(error,ArgError::MissingDependency(ArgType::Short("o".into()),ArgType::Short("f".into()),Some(1)));
+		assert_eq!(error.to_string(),"-o requires -f (argument 1)");
+		assert!(Args::new(to_args(&["-o","out.txt","-f","csv"]),format.clone()).is_ok());
+		//-f on its own does not need -o
+		assert!(Args::new(to_args(&["-f","csv"]),format).is_ok());
+	}
+	#[test]
+	fn fallback_conflicts_test(){
+		//a fallback that conflicts with the command line is ignored
+		std::env::set_var("ARGS_TEST_CONFLICT_NO_COLOUR","1");
+		let parser = Parser::new()
+			.opt(OptSpec::new("colour").long("colour").value(ValueType::String).group("colour"))
+			.opt(OptSpec::new("no-colour").long("no-colour").group("colour").env("ARGS_TEST_CONFLICT_NO_COLOUR"))
+			.opt(OptSpec::new("output").short("o").requires("colour"));
+		let args = parser.parse(to_args(&["--colour","never"])).unwrap();
+		assert!(!args.is_present("no-colour"));
+		assert!(parser.parse(vec![]).unwrap().is_present("no-colour"));
+		//--help skips dependencies but not conflicts
+		assert!(parser.parse(to_args(&["-o","--help"])).is_ok());
+		assert!(parser.parse(to_args(&["--colour","x","--no-colour","--help"])).is_err());
+		std::env::remove_var("ARGS_TEST_CONFLICT_NO_COLOUR");
 	}
 }
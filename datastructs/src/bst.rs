@@ -0,0 +1,461 @@
+//======================= binary search tree =======================
+//an AVL tree, so the height stays O(log n) even when the data goes in sorted
+use std::boxed::Box;
+use std::cmp::Ordering;
+use std::ops::{Bound,RangeBounds};
+mod map;
+pub use map::{BstMap,Entry,OccupiedEntry,VacantEntry,Iter as MapIter,IntoIter as MapIntoIter,Range as MapRange};
+
+//A keeps a summary of each subtree up to date for Bst::query, () for none
+pub struct Bst<T: std::cmp::PartialOrd, A: Augment<T> = ()>{
+	root: Link<T,A>,
+	len: usize,
+}
+type Link<T,A = ()> = Option<Box<BstNode<T,A>>>;
+struct BstNode<T, A: Augment<T> = ()>{
+	left: Link<T,A>,
+	right: Link<T,A>,
+	data: T,
+	//of the subtree starting here, a leaf is 1
+	height: usize,
+	//the number of items in the subtree, for nth and rank
+	size: usize,
+	summary: A::Summary,
+}
+//====== augmentation ======
+//a summary of a subtree that is kept up to date as the tree changes, so it can be asked for
+//over any range in O(log n). combine has to be associative, like + or max
+pub trait Augment<T>{
+	type Summary: Clone;
+	fn summarize(data: &T) -> Self::Summary;
+	//left is the items before right
+	fn combine(left: &Self::Summary, right: &Self::Summary) -> Self::Summary;
+}
+impl<T> Augment<T> for () {
+	type Summary = ();
+	fn summarize(_: &T) {}
+	fn combine(_: &(), _: &()) {}
+}
+//the total of the items
+pub struct Sum;
+impl<T: Clone + std::ops::Add<Output = T>> Augment<T> for Sum {
+	type Summary = T;
+	fn summarize(data: &T) -> T {
+		data.clone()
+	}
+	fn combine(left: &T, right: &T) -> T {
+		left.clone() + right.clone()
+	}
+}
+//the largest item, which is just Bst::max unless the ordering is on something else
+pub struct Max;
+impl<T: Clone + std::cmp::PartialOrd> Augment<T> for Max {
+	type Summary = T;
+	fn summarize(data: &T) -> T {
+		data.clone()
+	}
+	fn combine(left: &T, right: &T) -> T {
+		match left < right {
+			true => right.clone(),
+			false => left.clone(),
+		}
+	}
+}
+impl<T: std::cmp::PartialOrd> Bst<T>{
+	pub fn new() -> Self {
+		Self::augmented()
+	}
+}
+impl<T: std::cmp::PartialOrd, A: Augment<T>> Bst<T,A>{
+	//e.g. `Bst::<u32,Sum>::augmented()`
+	pub fn augmented() -> Self {
+		Self {
+			root: None,
+			len: 0,
+		}
+	}
+	//equal items are kept, so it works as a sorted multiset
+	pub fn add(&mut self, data: T){
+		//equal goes to the left, so it never counts as a match
+		let (root,_) = insert(self.root.take(),BstNode::new(data),&|new: &T,node: &T| match new <= node {
+			true => Ordering::Less,
+			false => Ordering::Greater,
+		});
+		self.root = Some(root);
+		self.len += 1;
+	}
+	pub fn contains(&self, data: &T) -> bool {
+		find(&self.root,|node| compare(data,node)).is_some()
+	}
+	//removes one copy of the item, false if it was not there
+	pub fn remove(&mut self, data: &T) -> bool {
+		let (root,removed) = remove(self.root.take(),&|node: &T| compare(data,node));
+		self.root = root;
+		if removed.is_some() {self.len -= 1}
+		removed.is_some()
+	}
+	pub fn min(&self) -> Option<&T> {
+		let mut node = self.root.as_ref()?;
+		while let Some(left) = &node.left {node = left}
+		Some(&node.data)
+	}
+	pub fn max(&self) -> Option<&T> {
+		let mut node = self.root.as_ref()?;
+		while let Some(right) = &node.right {node = right}
+		Some(&node.data)
+	}
+	pub fn len(&self) -> usize {
+		self.len
+	}
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+	//0 for an empty tree, at most about 1.44 log2(n)
+	pub fn height(&self) -> usize {
+		height(&self.root)
+	}
+	//====== traversal ======
+	//in order, smallest first
+	pub fn iter(&self) -> Iter<'_,T,A> {
+		Iter::new(&self.root)
+	}
+	pub fn pre_order(&self) -> PreOrder<'_,T,A> {
+		PreOrder {stack: self.root.as_deref().into_iter().collect()}
+	}
+	pub fn post_order(&self) -> PostOrder<'_,T,A> {
+		PostOrder {stack: self.root.as_deref().map(|node| (node,false)).into_iter().collect()}
+	}
+	//the items in the range in order, e.g. `bst.range(3..=7)`
+	pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_,T,R,A> {
+		let iter = Iter::starting_at(&self.root,|node| !below_start(range.start_bound(),node));
+		Range {iter, range}
+	}
+	pub fn as_vec(&self) -> Vec<&T>{
+		self.iter().collect()
+	}
+	//====== order statistics ======
+	//the item at index n in order, so nth(0) is the smallest
+	pub fn nth(&self, n: usize) -> Option<&T> {
+		nth(&self.root,n).map(|node| &node.data)
+	}
+	//how many items are less than the one given, which is its index if it is in the tree
+	pub fn rank(&self, data: &T) -> usize {
+		rank(&self.root,|node| data <= node)
+	}
+	//====== augmentation ======
+	//the summary of every item in the range, None if the range is empty
+	//e.g. with Sum, `bst.query(10..20)` adds up the items from 10 up to 20
+	pub fn query<R: RangeBounds<T>>(&self, range: R) -> Option<A::Summary> {
+		query(&self.root,&|node| below_start(range.start_bound(),node),&|node| past_end(range.end_bound(),node),false,false)
+	}
+	//the summary of the whole tree
+	pub fn summary(&self) -> Option<&A::Summary> {
+		self.root.as_ref().map(|root| &root.summary)
+	}
+}
+impl<T: std::cmp::PartialOrd, A: Augment<T>> Default for Bst<T,A>{
+	fn default() -> Self {
+		Self::augmented()
+	}
+}
+impl<'a, T: std::cmp::PartialOrd, A: Augment<T>> IntoIterator for &'a Bst<T,A>{
+	type Item = &'a T;
+	type IntoIter = Iter<'a,T,A>;
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+impl<T: std::cmp::PartialOrd, A: Augment<T>> FromIterator<T> for Bst<T,A>{
+	fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+		let mut bst = Self::augmented();
+		for data in iter {bst.add(data)}
+		bst
+	}
+}
+//====== iterators ======
+pub struct Iter<'a,T, A: Augment<T> = ()>{
+	//the nodes still to visit, the next one on top
+	stack: Vec<&'a BstNode<T,A>>,
+}
+impl<'a,T,A: Augment<T>> Iter<'a,T,A>{
+	fn new(root: &'a Link<T,A>) -> Self {
+		let mut iter = Self {stack: vec![]};
+		iter.push_left(root);
+		iter
+	}
+	//starts at the first item `in_range` is true for, it has to be false then true in order
+	fn starting_at(root: &'a Link<T,A>, in_range: impl Fn(&T) -> bool) -> Self {
+		let mut stack = vec![];
+		let mut link = root;
+		while let Some(node) = link {
+			if in_range(&node.data) {
+				stack.push(&**node);
+				link = &node.left;
+			}else{
+				link = &node.right;
+			}
+		}
+		Self {stack}
+	}
+	fn push_left(&mut self, mut link: &'a Link<T,A>){
+		while let Some(node) = link {
+			self.stack.push(node);
+			link = &node.left;
+		}
+	}
+}
+impl<'a,T,A: Augment<T>> Iterator for Iter<'a,T,A>{
+	type Item = &'a T;
+	fn next(&mut self) -> Option<Self::Item> {
+		let node = self.stack.pop()?;
+		self.push_left(&node.right);
+		Some(&node.data)
+	}
+}
+//each node before its children
+pub struct PreOrder<'a,T, A: Augment<T> = ()>{
+	stack: Vec<&'a BstNode<T,A>>,
+}
+impl<'a,T,A: Augment<T>> Iterator for PreOrder<'a,T,A>{
+	type Item = &'a T;
+	fn next(&mut self) -> Option<Self::Item> {
+		let node = self.stack.pop()?;
+		//left is on top so it comes out first
+		self.stack.extend(node.right.as_deref());
+		self.stack.extend(node.left.as_deref());
+		Some(&node.data)
+	}
+}
+//each node after its children
+pub struct PostOrder<'a,T, A: Augment<T> = ()>{
+	//whether the children have been pushed yet
+	stack: Vec<(&'a BstNode<T,A>,bool)>,
+}
+impl<'a,T,A: Augment<T>> Iterator for PostOrder<'a,T,A>{
+	type Item = &'a T;
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let (node,visited) = self.stack.pop()?;
+			if visited {return Some(&node.data)}
+			self.stack.push( (node,true) );
+			self.stack.extend(node.right.as_deref().map(|right| (right,false)));
+			self.stack.extend(node.left.as_deref().map(|left| (left,false)));
+		}
+	}
+}
+pub struct Range<'a,T,R, A: Augment<T> = ()>{
+	iter: Iter<'a,T,A>,
+	range: R,
+}
+impl<'a,T: std::cmp::PartialOrd,R: RangeBounds<T>,A: Augment<T>> Iterator for Range<'a,T,R,A>{
+	type Item = &'a T;
+	fn next(&mut self) -> Option<Self::Item> {
+		let data = self.iter.next()?;
+		if past_end(self.range.end_bound(),data) {
+			self.iter.stack.clear();
+			return None;
+		}
+		Some(data)
+	}
+}
+//====== bounds ======
+fn below_start<T: std::cmp::PartialOrd>(start: Bound<&T>, data: &T) -> bool {
+	match start {
+		Bound::Included(start) => data < start,
+		Bound::Excluded(start) => data <= start,
+		Bound::Unbounded => false,
+	}
+}
+fn past_end<T: std::cmp::PartialOrd>(end: Bound<&T>, data: &T) -> bool {
+	match end {
+		Bound::Included(end) => data > end,
+		Bound::Excluded(end) => data >= end,
+		Bound::Unbounded => false,
+	}
+}
+//incomparable values (NaN) never match
+fn compare<T: std::cmp::PartialOrd>(target: &T, node: &T) -> Ordering {
+	target.partial_cmp(node).unwrap_or(Ordering::Greater)
+}
+//======================= AVL balancing =======================
+//these take a closure giving where the item being looked for is compared to a node's
+//data, so the same code works for anything stored in the tree
+fn height<T,A: Augment<T>>(link: &Link<T,A>) -> usize {
+	link.as_ref().map_or(0,|node| node.height)
+}
+fn size<T,A: Augment<T>>(link: &Link<T,A>) -> usize {
+	link.as_ref().map_or(0,|node| node.size)
+}
+impl<T,A: Augment<T>> BstNode<T,A>{
+	fn new(data: T) -> Box<Self> {
+		let summary = A::summarize(&data);
+		Box::new(Self {left: None, right: None, data, height: 1, size: 1, summary})
+	}
+	//after the children change
+	fn update(&mut self){
+		self.height = 1 + height(&self.left).max(height(&self.right));
+		self.size = 1 + size(&self.left) + size(&self.right);
+		let mut summary = A::summarize(&self.data);
+		if let Some(left) = &self.left {summary = A::combine(&left.summary,&summary)}
+		if let Some(right) = &self.right {summary = A::combine(&summary,&right.summary)}
+		self.summary = summary;
+	}
+}
+fn rotate_left<T,A: Augment<T>>(mut node: Box<BstNode<T,A>>) -> Box<BstNode<T,A>> {
+	let Some(mut right) = node.right.take() else {return node};
+	node.right = right.left.take();
+	node.update();
+	right.left = Some(node);
+	right.update();
+	right
+}
+fn rotate_right<T,A: Augment<T>>(mut node: Box<BstNode<T,A>>) -> Box<BstNode<T,A>> {
+	let Some(mut left) = node.left.take() else {return node};
+	node.left = left.right.take();
+	node.update();
+	left.right = Some(node);
+	left.update();
+	left
+}
+//fixes the node up after one of its subtrees changed height by one
+fn rebalance<T,A: Augment<T>>(mut node: Box<BstNode<T,A>>) -> Box<BstNode<T,A>> {
+	node.update();
+	let (left,right) = (height(&node.left),height(&node.right));
+	if left > right + 1 {
+		//====== left heavy ======
+		if let Some(child) = node.left.take() {
+			node.left = Some(match height(&child.left) < height(&child.right) {
+				true => rotate_left(child),
+				false => child,
+			});
+		}
+		return rotate_right(node);
+	}
+	if right > left + 1 {
+		//====== right heavy ======
+		if let Some(child) = node.right.take() {
+			node.right = Some(match height(&child.right) < height(&child.left) {
+				true => rotate_right(child),
+				false => child,
+			});
+		}
+		return rotate_left(node);
+	}
+	node
+}
+//cmp gives where the new node goes compared to each node, Equal replaces the node's data
+//gives back the new root and the data that was replaced
+fn insert<T,A: Augment<T>>(link: Link<T,A>, new: Box<BstNode<T,A>>, cmp: &impl Fn(&T,&T) -> Ordering) -> (Box<BstNode<T,A>>,Option<T>) {
+	let Some(mut node) = link else {return (new,None)};
+	let replaced = match cmp(&new.data,&node.data) {
+		Ordering::Less => {
+			let (left,replaced) = insert(node.left.take(),new,cmp);
+			node.left = Some(left);
+			replaced
+		},
+		Ordering::Greater => {
+			let (right,replaced) = insert(node.right.take(),new,cmp);
+			node.right = Some(right);
+			replaced
+		},
+		Ordering::Equal => {
+			let BstNode {data,..} = *new;
+			let old = std::mem::replace(&mut node.data,data);
+			node.update();
+			return (node,Some(old));
+		},
+	};
+	(rebalance(node),replaced)
+}
+fn find<T,A: Augment<T>>(mut link: &Link<T,A>, cmp: impl Fn(&T) -> Ordering) -> Option<&BstNode<T,A>> {
+	while let Some(node) = link {
+		link = match cmp(&node.data) {
+			Ordering::Less => &node.left,
+			Ordering::Greater => &node.right,
+			Ordering::Equal => return Some(node),
+		};
+	}
+	None
+}
+//gives back the new root and the removed data
+fn remove<T,A: Augment<T>>(link: Link<T,A>, cmp: &impl Fn(&T) -> Ordering) -> (Link<T,A>,Option<T>) {
+	let Some(mut node) = link else {return (None,None)};
+	let removed = match cmp(&node.data) {
+		Ordering::Less => {
+			let (left,removed) = remove(node.left.take(),cmp);
+			node.left = left;
+			removed
+		},
+		Ordering::Greater => {
+			let (right,removed) = remove(node.right.take(),cmp);
+			node.right = right;
+			removed
+		},
+		Ordering::Equal => {
+			let BstNode {left,right,data,..} = *node;
+			let Some(right) = right else {return (left,Some(data))};
+			//the smallest item on the right takes this node's place
+			let (right,successor) = remove_min(right);
+			let mut replacement = BstNode::new(successor);
+			replacement.left = left;
+			replacement.right = right;
+			return (Some(rebalance(replacement)),Some(data));
+		},
+	};
+	(Some(rebalance(node)),removed)
+}
+fn remove_min<T,A: Augment<T>>(mut node: Box<BstNode<T,A>>) -> (Link<T,A>,T) {
+	match node.left.take() {
+		Some(left) => {
+			let (left,min) = remove_min(left);
+			node.left = left;
+			(Some(rebalance(node)),min)
+		},
+		None => {
+			let BstNode {right,data,..} = *node;
+			(right,data)
+		},
+	}
+}
+//====== order statistics ======
+fn nth<T,A: Augment<T>>(mut link: &Link<T,A>, mut n: usize) -> Option<&BstNode<T,A>> {
+	while let Some(node) = link {
+		let left = size(&node.left);
+		link = match n.cmp(&left) {
+			Ordering::Less => &node.left,
+			Ordering::Equal => return Some(node),
+			Ordering::Greater => {
+				n -= left + 1;
+				&node.right
+			},
+		};
+	}
+	None
+}
+//the number of items before the first one `at_or_after` is true for
+fn rank<T,A: Augment<T>>(mut link: &Link<T,A>, at_or_after: impl Fn(&T) -> bool) -> usize {
+	let mut rank = 0;
+	while let Some(node) = link {
+		link = match at_or_after(&node.data) {
+			true => &node.left,
+			false => {
+				rank += size(&node.left) + 1;
+				&node.right
+			},
+		};
+	}
+	rank
+}
+//low and high are true once every item in the subtree is known to be past the start or
+//before the end, at that point the whole subtree's summary can be used
+fn query<T,A: Augment<T>>(link: &Link<T,A>, below_start: &impl Fn(&T) -> bool, past_end: &impl Fn(&T) -> bool, low: bool, high: bool) -> Option<A::Summary> {
+	let node = link.as_ref()?;
+	if low && high {return Some(node.summary.clone())}
+	//the node and everything on one side of it is out of the range
+	if !low && below_start(&node.data) {return query(&node.right,below_start,past_end,low,high)}
+	if !high && past_end(&node.data) {return query(&node.left,below_start,past_end,low,high)}
+	let mut summary = A::summarize(&node.data);
+	if let Some(left) = query(&node.left,below_start,past_end,low,true) {summary = A::combine(&left,&summary)}
+	if let Some(right) = query(&node.right,below_start,past_end,true,high) {summary = A::combine(&summary,&right)}
+	Some(summary)
+}
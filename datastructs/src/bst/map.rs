@@ -0,0 +1,282 @@
+//======================= ordered map =======================
+//the same AVL tree as Bst, with (key,value) pairs compared by key
+use super::{BstNode,Link,below_start,past_end,insert,find,remove,nth,rank,size};
+use std::ops::RangeBounds;
+
+pub struct BstMap<K: Ord,V>{
+	root: Link<(K,V)>,
+	len: usize,
+}
+impl<K: Ord,V> BstMap<K,V>{
+	pub fn new() -> Self {
+		Self {
+			root: None,
+			len: 0,
+		}
+	}
+	//gives back the old value if the key was already there
+	pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+		let (root,replaced) = insert(self.root.take(),BstNode::new((key,value)),&|new: &(K,V),node: &(K,V)| new.0.cmp(&node.0));
+		self.root = Some(root);
+		if replaced.is_none() {self.len += 1}
+		replaced.map(|(_,value)| value)
+	}
+	pub fn get(&self, key: &K) -> Option<&V> {
+		find(&self.root,|node| key.cmp(&node.0)).map(|node| &node.data.1)
+	}
+	pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+		let mut link = &mut self.root;
+		while let Some(node) = link {
+			link = match key.cmp(&node.data.0) {
+				std::cmp::Ordering::Less => &mut node.left,
+				std::cmp::Ordering::Greater => &mut node.right,
+				std::cmp::Ordering::Equal => return Some(&mut node.data.1),
+			};
+		}
+		None
+	}
+	pub fn contains_key(&self, key: &K) -> bool {
+		self.get(key).is_some()
+	}
+	pub fn remove(&mut self, key: &K) -> Option<V> {
+		let (root,removed) = remove(self.root.take(),&|node: &(K,V)| key.cmp(&node.0));
+		self.root = root;
+		if removed.is_some() {self.len -= 1}
+		removed.map(|(_,value)| value)
+	}
+	//e.g. `*map.entry(word).or_insert(0) += 1`
+	pub fn entry(&mut self, key: K) -> Entry<'_,K,V> {
+		match self.contains_key(&key) {
+			true => Entry::Occupied(OccupiedEntry {value: self.get_mut(&key).unwrap(), key}),
+			false => Entry::Vacant(VacantEntry {map: self, key}),
+		}
+	}
+	pub fn len(&self) -> usize {
+		self.len
+	}
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+	//====== ordered lookups ======
+	//the largest key that is less than or equal to the one given
+	pub fn floor(&self, key: &K) -> Option<(&K,&V)> {
+		let mut best = None;
+		let mut link = &self.root;
+		while let Some(node) = link {
+			link = match key.cmp(&node.data.0) {
+				std::cmp::Ordering::Less => &node.left,
+				std::cmp::Ordering::Greater => {
+					best = Some(node);
+					&node.right
+				},
+				std::cmp::Ordering::Equal => return Some(pair(node)),
+			};
+		}
+		best.map(|node| pair(node))
+	}
+	//the smallest key that is greater than or equal to the one given
+	pub fn ceiling(&self, key: &K) -> Option<(&K,&V)> {
+		let mut best = None;
+		let mut link = &self.root;
+		while let Some(node) = link {
+			link = match key.cmp(&node.data.0) {
+				std::cmp::Ordering::Less => {
+					best = Some(node);
+					&node.left
+				},
+				std::cmp::Ordering::Greater => &node.right,
+				std::cmp::Ordering::Equal => return Some(pair(node)),
+			};
+		}
+		best.map(|node| pair(node))
+	}
+	//the entry at index n in key order
+	pub fn nth(&self, n: usize) -> Option<(&K,&V)> {
+		nth(&self.root,n).map(pair)
+	}
+	//how many keys are less than the one given
+	pub fn rank(&self, key: &K) -> usize {
+		rank(&self.root,|(node_key,_)| key <= node_key)
+	}
+	pub fn first(&self) -> Option<(&K,&V)> {
+		self.iter().next()
+	}
+	pub fn last(&self) -> Option<(&K,&V)> {
+		let mut node = self.root.as_ref()?;
+		while let Some(right) = &node.right {node = right}
+		Some(pair(node))
+	}
+	//====== iteration ======
+	//in key order
+	pub fn iter(&self) -> Iter<'_,K,V> {
+		Iter(super::Iter::new(&self.root))
+	}
+	pub fn keys(&self) -> impl Iterator<Item = &K> {
+		self.iter().map(|(key,_)| key)
+	}
+	pub fn values(&self) -> impl Iterator<Item = &V> {
+		self.iter().map(|(_,value)| value)
+	}
+	//the entries with keys in the range, e.g. `map.range("a".."c")`
+	pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_,K,V,R> {
+		let iter = super::Iter::starting_at(&self.root,|(key,_)| !below_start(range.start_bound(),key));
+		Range {iter, range}
+	}
+}
+fn pair<K,V>(node: &BstNode<(K,V)>) -> (&K,&V) {
+	(&node.data.0,&node.data.1)
+}
+impl<K: Ord,V> Default for BstMap<K,V>{
+	fn default() -> Self {
+		Self::new()
+	}
+}
+impl<K: Ord,V> FromIterator<(K,V)> for BstMap<K,V>{
+	fn from_iter<I: IntoIterator<Item = (K,V)>>(iter: I) -> Self {
+		let mut map = Self::new();
+		for (key,value) in iter {map.insert(key,value);}
+		map
+	}
+}
+//====== entries ======
+pub enum Entry<'a,K: Ord,V>{
+	Occupied(OccupiedEntry<'a,K,V>),
+	Vacant(VacantEntry<'a,K,V>),
+}
+pub struct OccupiedEntry<'a,K,V>{
+	key: K,
+	value: &'a mut V,
+}
+pub struct VacantEntry<'a,K: Ord,V>{
+	map: &'a mut BstMap<K,V>,
+	key: K,
+}
+impl<'a,K: Ord,V> Entry<'a,K,V>{
+	pub fn key(&self) -> &K {
+		match self {
+			Entry::Occupied(entry) => &entry.key,
+			Entry::Vacant(entry) => &entry.key,
+		}
+	}
+	pub fn or_insert(self, default: V) -> &'a mut V {
+		self.or_insert_with(|| default)
+	}
+	pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'a mut V {
+		match self {
+			Entry::Occupied(entry) => entry.into_mut(),
+			Entry::Vacant(entry) => entry.insert(default()),
+		}
+	}
+	pub fn or_default(self) -> &'a mut V where V: Default {
+		self.or_insert_with(V::default)
+	}
+	pub fn and_modify(mut self, modify: impl FnOnce(&mut V)) -> Self {
+		if let Entry::Occupied(entry) = &mut self {modify(entry.value)}
+		self
+	}
+}
+impl<'a,K,V> OccupiedEntry<'a,K,V>{
+	pub fn key(&self) -> &K {
+		&self.key
+	}
+	pub fn get(&self) -> &V {
+		self.value
+	}
+	pub fn get_mut(&mut self) -> &mut V {
+		self.value
+	}
+	pub fn into_mut(self) -> &'a mut V {
+		self.value
+	}
+	//gives back the old value
+	pub fn insert(&mut self, value: V) -> V {
+		std::mem::replace(self.value,value)
+	}
+}
+impl<'a,K: Ord,V> VacantEntry<'a,K,V>{
+	pub fn key(&self) -> &K {
+		&self.key
+	}
+	pub fn insert(self, value: V) -> &'a mut V {
+		let VacantEntry {map,key} = self;
+		//the key is moved into the tree so it can not be used to find the value again,
+		//but its index in key order stays the same through the rebalancing
+		let index = map.rank(&key);
+		let (root,_) = insert(map.root.take(),BstNode::new((key,value)),&|new: &(K,V),node: &(K,V)| new.0.cmp(&node.0));
+		map.root = Some(root);
+		map.len += 1;
+		let mut link = &mut map.root;
+		let mut n = index;
+		while let Some(node) = link {
+			let left = size(&node.left);
+			link = match n.cmp(&left) {
+				std::cmp::Ordering::Less => &mut node.left,
+				std::cmp::Ordering::Equal => return &mut node.data.1,
+				std::cmp::Ordering::Greater => {
+					n -= left + 1;
+					&mut node.right
+				},
+			};
+		}
+		unreachable!("the entry was just inserted")
+	}
+}
+//====== iterators ======
+pub struct Iter<'a,K,V>(super::Iter<'a,(K,V)>);
+impl<'a,K,V> Iterator for Iter<'a,K,V>{
+	type Item = (&'a K,&'a V);
+	fn next(&mut self) -> Option<Self::Item> {
+		self.0.next().map(|(key,value)| (key,value))
+	}
+}
+pub struct Range<'a,K,V,R>{
+	iter: super::Iter<'a,(K,V)>,
+	range: R,
+}
+impl<'a,K: Ord,V,R: RangeBounds<K>> Iterator for Range<'a,K,V,R>{
+	type Item = (&'a K,&'a V);
+	fn next(&mut self) -> Option<Self::Item> {
+		let (key,value) = self.iter.next()?;
+		if past_end(self.range.end_bound(),key) {
+			self.iter.stack.clear();
+			return None;
+		}
+		Some( (key,value) )
+	}
+}
+//takes the tree apart as it goes
+pub struct IntoIter<K,V>{
+	stack: Vec<Box<BstNode<(K,V)>>>,
+}
+impl<K,V> IntoIter<K,V>{
+	fn push_left(&mut self, mut link: Link<(K,V)>){
+		while let Some(mut node) = link {
+			link = node.left.take();
+			self.stack.push(node);
+		}
+	}
+}
+impl<K,V> Iterator for IntoIter<K,V>{
+	type Item = (K,V);
+	fn next(&mut self) -> Option<Self::Item> {
+		let mut node = self.stack.pop()?;
+		self.push_left(node.right.take());
+		Some(node.data)
+	}
+}
+impl<K: Ord,V> IntoIterator for BstMap<K,V>{
+	type Item = (K,V);
+	type IntoIter = IntoIter<K,V>;
+	fn into_iter(mut self) -> Self::IntoIter {
+		let mut iter = IntoIter {stack: vec![]};
+		iter.push_left(self.root.take());
+		iter
+	}
+}
+impl<'a,K: Ord,V> IntoIterator for &'a BstMap<K,V>{
+	type Item = (&'a K,&'a V);
+	type IntoIter = Iter<'a,K,V>;
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
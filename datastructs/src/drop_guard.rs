@@ -0,0 +1,97 @@
+//======================= drop guard =======================
+//owns a value and hands it to a callback when dropped, unless it is dismissed first
+// let file = DropGuard::new(File::create(path)?,|_| {let _ = fs::remove_file(path);});
+// write_everything(&file)?; //the file is removed if this fails
+// DropGuard::dismiss(&mut file);
+use std::ops::{Deref,DerefMut};
+
+pub struct DropGuard<T, F: FnOnce(T)> {
+	//only None once into_inner has taken it
+	value: Option<T>,
+	//None once dismissed
+	callback: Option<F>,
+	when: When,
+}
+//when the callback runs
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum When {
+	Always,
+	//only when the scope is left normally
+	Success,
+	//only when leaving because of a panic
+	Unwind,
+}
+impl<T, F: FnOnce(T)> DropGuard<T,F> {
+	pub fn new(value: T, callback: F) -> Self {
+		Self::when(value,callback,When::Always)
+	}
+	pub fn on_success(value: T, callback: F) -> Self {
+		Self::when(value,callback,When::Success)
+	}
+	pub fn on_unwind(value: T, callback: F) -> Self {
+		Self::when(value,callback,When::Unwind)
+	}
+	pub fn when(value: T, callback: F, when: When) -> Self {
+		Self {
+			value: Some(value),
+			callback: Some(callback),
+			when,
+		}
+	}
+	//these are not methods so they do not get mixed up with the value's own methods
+	//stops the callback from running, the value is still dropped as normal
+	pub fn dismiss(guard: &mut Self){
+		guard.callback = None;
+	}
+	//takes the value back without running the callback
+	pub fn into_inner(mut guard: Self) -> T {
+		guard.callback = None;
+		guard.value.take().unwrap()
+	}
+}
+impl<T, F: FnOnce(T)> Deref for DropGuard<T,F> {
+	type Target = T;
+	fn deref(&self) -> &T {
+		self.value.as_ref().unwrap()
+	}
+}
+impl<T, F: FnOnce(T)> DerefMut for DropGuard<T,F> {
+	fn deref_mut(&mut self) -> &mut T {
+		self.value.as_mut().unwrap()
+	}
+}
+impl<T, F: FnOnce(T)> Drop for DropGuard<T,F> {
+	fn drop(&mut self){
+		let run = match self.when {
+			When::Always => true,
+			When::Success => !std::thread::panicking(),
+			When::Unwind => std::thread::panicking(),
+		};
+		if let (true,Some(value),Some(callback)) = (run,self.value.take(),self.callback.take()) {
+			callback(value);
+		}
+	}
+}
+//====== scope macros ======
+//runs the code at the end of the scope, however it is left
+// defer!{println!("done")}
+#[macro_export]
+macro_rules! defer {
+	($($body:tt)*) => {
+		let _guard = $crate::drop_guard::DropGuard::new((),|()| {$($body)*});
+	};
+}
+//runs the code at the end of the scope unless it is left by a panic
+#[macro_export]
+macro_rules! defer_on_success {
+	($($body:tt)*) => {
+		let _guard = $crate::drop_guard::DropGuard::on_success((),|()| {$($body)*});
+	};
+}
+//runs the code only if the scope is left by a panic, e.g. to roll something back
+#[macro_export]
+macro_rules! defer_on_unwind {
+	($($body:tt)*) => {
+		let _guard = $crate::drop_guard::DropGuard::on_unwind((),|()| {$($body)*});
+	};
+}
@@ -1,84 +1,6 @@
-//======================= binary search tree =======================
-mod bst {
-	use std::boxed::Box;
-
-	pub struct Bst<T: std::cmp::PartialOrd>{
-		root: Option<Box<BstNode<T>>>,
-	}
-	struct BstNode<T: std::cmp::PartialOrd>{
-		left: Option<Box<BstNode<T>>>,
-		right: Option<Box<BstNode<T>>>,
-		data: T,
-	}
-	impl<T: std::cmp::PartialOrd> Bst<T>{
-		pub fn new() -> Self {
-			Self {
-				root: None,
-			}
-		}
-		pub fn add(&mut self, data: T){
-			if self.root.is_none(){
-				self.root = Some(Box::new(BstNode {
-					left: None,
-					right: None,
-					data: data
-				}))
-			}else{
-				Self::recursive_add(&mut self.root.as_mut().unwrap(),
-					Box::new(BstNode {
-						left: None,
-						right: None,
-						data: data
-					}));
-			}
-		}
-		fn recursive_add(node: &mut Box<BstNode<T>>, new_node: Box<BstNode<T>>){
-			if new_node.data <= node.data {
-				//add to the left
-				match &mut node.left {
-					Some(n) => Self::recursive_add(n,new_node),
-					None => node.left = Some(new_node),
-				}
-			}else{
-				//add to the right
-				match &mut node.right {
-					Some(n) => Self::recursive_add(n,new_node),
-					None => node.right = Some(new_node),
-				}
-			}
-		}
-		pub fn as_vec(&self) -> Vec<&T>{
-			 fn traverse<'a, T: std::cmp::PartialOrd>(bst_node: &'a Option<Box<BstNode<T>>>,output: &mut Vec<&'a T>){ //inorder
-				if let Some(node) = bst_node {
-					traverse(&node.left,output);
-					output.push(&node.data);
-					traverse(&node.right,output);
-				}
-			}
-			let mut result = vec![];
-			traverse(&self.root,&mut result);
-			result
-		}
-	}
-}
-//======================= drop guard =======================
-mod drop_guard {
-	pub struct DropGuard<T: Fn()> {
-		callback: T,
-	}
-	impl<T: Fn()> DropGuard<T> {
-		fn drop(callback: T) -> Self {
-			Self {
-				callback: callback,
-			}
-		}
-	}
-	impl<T: Fn()> Drop for DropGuard<T> {
-		fn drop(&mut self){
-			(self.callback)();
-		}
-	}
-}
+pub mod bst;
+pub mod drop_guard;
+pub mod priority_queue;
 //======================= tests =======================
 #[cfg(test)]
 mod tests {
@@ -97,4 +19,222 @@ mod tests {
 		bst.as_vec().into_iter().cloned().collect::<Vec<i32>>()
 	);
     }
+    #[test]
+    fn bst_balanced(){
+	    //sorted input used to make it a linked list
+	    let mut bst = (0..1000).collect::<bst::Bst<i32>>();
+	    assert_eq!(bst.len(),1000);
+	    assert!(bst.height() <= 14);
+	    assert!(bst.contains(&500));
+	    assert!(!bst.contains(&1000));
+	    for i in (0..1000).step_by(2) {
+		    assert!(bst.remove(&i));
+	    }
+	    assert!(!bst.remove(&0));
+	    assert_eq!(bst.len(),500);
+	    assert!(bst.height() <= 13);
+	    assert_eq!(bst.min(),Some(&1));
+	    assert_eq!(bst.max(),Some(&999));
+	    assert_eq!(bst.iter().cloned().collect::<Vec<_>>(),(1..1000).step_by(2).collect::<Vec<_>>());
+	    //duplicates are kept
+	    bst.add(7);
+	    assert_eq!(bst.range(5..=9).cloned().collect::<Vec<_>>(),vec![5,7,7,9]);
+	    assert!(bst.remove(&7));
+	    assert!(bst.contains(&7));
+	    let empty = bst::Bst::<i32>::new();
+	    assert!(empty.is_empty());
+	    assert_eq!(empty.min(),None);
+	    assert_eq!(empty.iter().next(),None);
+    }
+    #[test]
+    fn bst_traversal(){
+	    //     4
+	    //   2   6
+	    //  1 3 5 7
+	    let bst = [4,2,6,1,3,5,7].into_iter().collect::<bst::Bst<i32>>();
+	    assert_eq!(bst.pre_order().cloned().collect::<Vec<_>>(),vec![4,2,1,3,6,5,7]);
+	    assert_eq!(bst.post_order().cloned().collect::<Vec<_>>(),vec![1,3,2,5,7,6,4]);
+	    assert_eq!((&bst).into_iter().cloned().collect::<Vec<_>>(),vec![1,2,3,4,5,6,7]);
+	    assert_eq!(bst.range(3..6).cloned().collect::<Vec<_>>(),vec![3,4,5]);
+	    assert_eq!(bst.range(..3).cloned().collect::<Vec<_>>(),vec![1,2]);
+	    assert_eq!(bst.range((std::ops::Bound::Excluded(5),std::ops::Bound::Unbounded)).cloned().collect::<Vec<_>>(),vec![6,7]);
+	    assert_eq!(bst.range(8..).next(),None);
+	    let floats = [2.5,f64::NAN,1.0].into_iter().collect::<bst::Bst<f64>>();
+	    assert!(floats.contains(&1.0));
+	    assert!(!floats.contains(&f64::NAN));
+    }
+    #[test]
+    fn bst_map(){
+	    let mut map = bst::BstMap::new();
+	    for (i,word) in ["pear","apple","fig","kiwi","banana"].into_iter().enumerate() {
+		    assert_eq!(map.insert(word,i),None);
+	    }
+	    assert_eq!(map.insert("fig",20),Some(2));
+	    assert_eq!(map.len(),5);
+	    assert_eq!(map.get(&"fig"),Some(&20));
+	    *map.get_mut(&"kiwi").unwrap() += 10;
+	    assert_eq!(map.get(&"kiwi"),Some(&13));
+	    assert_eq!(map.get(&"plum"),None);
+	    //====== ordered lookups ======
+	    assert_eq!(map.keys().cloned().collect::<Vec<_>>(),vec!["apple","banana","fig","kiwi","pear"]);
+	    assert_eq!(map.floor(&"cherry"),Some((&"banana",&4)));
+	    assert_eq!(map.floor(&"fig"),Some((&"fig",&20)));
+	    assert_eq!(map.floor(&"aardvark"),None);
+	    assert_eq!(map.ceiling(&"cherry"),Some((&"fig",&20)));
+	    assert_eq!(map.ceiling(&"zebra"),None);
+	    assert_eq!(map.first(),Some((&"apple",&1)));
+	    assert_eq!(map.last(),Some((&"pear",&0)));
+	    assert_eq!(map.range("b".."l").map(|(key,_)| *key).collect::<Vec<_>>(),vec!["banana","fig","kiwi"]);
+	    //====== entries ======
+	    let mut counts = bst::BstMap::new();
+	    for word in "a b a c b a".split(' ') {
+		    *counts.entry(word).or_insert(0) += 1;
+	    }
+	    counts.entry("c").and_modify(|count| *count *= 10).or_default();
+	    counts.entry("d").and_modify(|count| *count *= 10).or_default();
+	    assert_eq!((&counts).into_iter().collect::<Vec<_>>(),vec![(&"a",&3),(&"b",&2),(&"c",&10),(&"d",&0)]);
+	    //the reference from a vacant entry points at the value in the tree
+	    let mut spread = (0..50).map(|i| (i * 2,i)).collect::<bst::BstMap<_,_>>();
+	    for key in [51,1,99,-1] {
+		    *spread.entry(key).or_insert(0) = key * 100;
+		    assert_eq!(spread.get(&key),Some(&(key * 100)));
+	    }
+	    assert_eq!(spread.get(&50),Some(&25));
+	    assert_eq!(spread.len(),54);
+	    if let bst::Entry::Occupied(mut entry) = counts.entry("a") {
+		    assert_eq!(entry.insert(7),3);
+	    }
+	    //====== removal ======
+	    assert_eq!(map.remove(&"fig"),Some(20));
+	    assert_eq!(map.remove(&"fig"),None);
+	    assert_eq!(map.len(),4);
+	    assert_eq!(map.into_iter().collect::<Vec<_>>(),vec![("apple",1),("banana",4),("kiwi",13),("pear",0)]);
+	    let big = (0..1000).map(|i| (i,i * 2)).collect::<bst::BstMap<_,_>>();
+	    assert_eq!(big.get(&999),Some(&1998));
+	    assert_eq!(counts.get(&"a"),Some(&7));
+    }
+    #[test]
+    fn bst_order_statistics(){
+	    let mut bst = (0..100).map(|i| i * 10).collect::<bst::Bst<i32>>();
+	    assert_eq!(bst.nth(0),Some(&0));
+	    assert_eq!(bst.nth(42),Some(&420));
+	    assert_eq!(bst.nth(100),None);
+	    assert_eq!(bst.rank(&420),42);
+	    assert_eq!(bst.rank(&425),43);
+	    assert_eq!(bst.rank(&-1),0);
+	    bst.remove(&0);
+	    bst.add(5);
+	    bst.add(5);
+	    assert_eq!(bst.rank(&10),2);
+	    assert_eq!(bst.nth(1),Some(&5));
+	    let map = ["c","a","b"].into_iter().map(|key| (key,())).collect::<bst::BstMap<_,_>>();
+	    assert_eq!(map.nth(1),Some((&"b",&())));
+	    assert_eq!(map.rank(&"c"),2);
+    }
+    #[test]
+    fn bst_augmented(){
+	    let mut sums = (1..=100).collect::<bst::Bst<u32,bst::Sum>>();
+	    assert_eq!(sums.summary(),Some(&5050));
+	    assert_eq!(sums.query(1..=10),Some(55));
+	    assert_eq!(sums.query(11..),Some(5050 - 55));
+	    assert_eq!(sums.query(200..),None);
+	    sums.remove(&10);
+	    assert_eq!(sums.query(..=10),Some(45));
+	    //====== intervals, ordered by start with the latest end in each subtree ======
+	    struct MaxEnd;
+	    impl bst::Augment<(i32,i32)> for MaxEnd {
+		    type Summary = i32;
+		    fn summarize(interval: &(i32,i32)) -> i32 {
+			    interval.1
+		    }
+		    fn combine(left: &i32, right: &i32) -> i32 {
+			    *left.max(right)
+		    }
+	    }
+	    let intervals = [(1,3),(2,8),(5,6),(10,12),(15,20)].into_iter().collect::<bst::Bst<_,MaxEnd>>();
+	    //something overlaps a..b if it starts by b and ends after a
+	    let overlaps = |a: i32,b: i32| intervals.query(..=(b,i32::MAX)).is_some_and(|end| end >= a);
+	    assert!(overlaps(7,9));
+	    assert!(!overlaps(9,9));
+	    assert!(overlaps(13,16));
+	    assert!(!overlaps(21,30));
+	    let max = [3,9,1].into_iter().collect::<bst::Bst<i32,bst::Max>>();
+	    assert_eq!(max.query(..5),Some(3));
+    }
+    #[test]
+    fn drop_guard(){
+	    use drop_guard::DropGuard;
+	    use std::cell::RefCell;
+	    let log = RefCell::new(vec![]);
+	    {
+		    let mut guard = DropGuard::new(vec![1],|value| log.borrow_mut().push(value.len()));
+		    guard.push(2);
+	    }
+	    assert_eq!(*log.borrow(),vec![2]);
+	    let mut dismissed = DropGuard::new(3,|value| log.borrow_mut().push(value));
+	    DropGuard::dismiss(&mut dismissed);
+	    drop(dismissed);
+	    let kept = DropGuard::new(4,|value| log.borrow_mut().push(value));
+	    assert_eq!(DropGuard::into_inner(kept),4);
+	    assert_eq!(*log.borrow(),vec![2]);
+	    //====== macros ======
+	    {
+		    defer!{log.borrow_mut().push(10)}
+		    defer_on_success!{log.borrow_mut().push(11)}
+		    defer_on_unwind!{log.borrow_mut().push(12)}
+		    log.borrow_mut().push(9);
+	    }
+	    //in reverse order, like any other drop
+	    assert_eq!(*log.borrow(),vec![2,9,11,10]);
+	    let unwound = std::sync::Mutex::new(vec![]);
+	    let result = std::panic::catch_unwind(|| {
+		    defer_on_unwind!{unwound.lock().unwrap().push("unwind")}
+		    defer_on_success!{unwound.lock().unwrap().push("success")}
+		    panic!("oh no");
+	    });
+	    assert!(result.is_err());
+	    assert_eq!(*unwound.lock().unwrap(),vec!["unwind"]);
+    }
+    #[test]
+    fn priority_queue(){
+	    use priority_queue::PriorityQueue;
+	    let mut queue = PriorityQueue::new();
+	    let tio = queue.push("tio",1);
+	    let lwrap = queue.push("lwrap",2);
+	    let args = queue.push("args",5);
+	    queue.push("sl",4);
+	    assert_eq!(queue.peek(),Some((&"args",&5)));
+	    assert_eq!(queue.len(),4);
+	    //====== by handle ======
+	    assert_eq!(queue.change_priority(tio,10),Some(1));
+	    assert_eq!(queue.change_priority(args,0),Some(5));
+	    assert_eq!(queue.get(lwrap),Some((&"lwrap",&2)));
+	    assert_eq!(queue.remove(lwrap),Some(("lwrap",2)));
+	    assert_eq!(queue.remove(lwrap),None);
+	    assert!(!queue.contains(lwrap));
+	    assert_eq!(queue.pop(),Some(("tio",10)));
+	    assert_eq!(queue.change_priority(tio,3),None);
+	    //a new item reusing the slot does not answer to the old handle
+	    let mc = queue.push("mc",7);
+	    assert_eq!(queue.get(tio),None);
+	    assert_eq!(queue.get(mc),Some((&"mc",&7)));
+	    let order = std::iter::from_fn(|| queue.pop()).collect::<Vec<_>>();
+	    assert_eq!(order,vec![("mc",7),("sl",4),("args",0)]);
+	    assert!(queue.is_empty());
+	    assert_eq!(queue.pop(),None);
+	    //====== against a sort ======
+	    let mut queue = (0..200).map(|i| (i,(i * 37) % 101)).collect::<PriorityQueue<_,_>>();
+	    let handles = (0..200).map(|i| queue.push(i + 200,0)).collect::<Vec<_>>();
+	    for (i,handle) in handles.iter().enumerate() {
+		    match i % 3 {
+			    0 => {queue.remove(*handle);},
+			    _ => {queue.change_priority(*handle,(i * 13) % 97);},
+		    }
+	    }
+	    let popped = std::iter::from_fn(|| queue.pop()).map(|(_,priority)| priority).collect::<Vec<_>>();
+	    let mut sorted = popped.clone();
+	    sorted.sort_by(|a,b| b.cmp(a));
+	    assert_eq!(popped,sorted);
+	    assert_eq!(popped.len(),200 + 133);
+    }
 }
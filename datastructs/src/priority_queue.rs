@@ -0,0 +1,151 @@
+//======================= indexed priority queue =======================
+//a binary heap that gives out a handle for each item, so its priority can be changed or
+//it can be removed later. Highest priority first like BinaryHeap, use Reverse for lowest
+// let mut queue = PriorityQueue::new();
+// let build = queue.push("tio",1);
+// queue.push("lwrap",2);
+// queue.change_priority(build,3);
+// assert_eq!(queue.pop(),Some(("tio",3)));
+pub struct PriorityQueue<T, P: Ord> {
+	heap: Vec<HeapEntry<T,P>>,
+	//indexed by Handle::slot
+	slots: Vec<Slot>,
+	//slots that are not in use
+	free: Vec<usize>,
+}
+struct HeapEntry<T,P>{
+	item: T,
+	priority: P,
+	slot: usize,
+}
+struct Slot {
+	//where the item is in the heap, None once it has been popped or removed
+	position: Option<usize>,
+	//goes up each time the slot is reused, so old handles stop working
+	generation: usize,
+}
+//refers to one item that was pushed, it stops working once the item is popped or removed
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct Handle {
+	slot: usize,
+	generation: usize,
+}
+impl<T, P: Ord> PriorityQueue<T,P> {
+	pub fn new() -> Self {
+		Self {
+			heap: vec![],
+			slots: vec![],
+			free: vec![],
+		}
+	}
+	pub fn push(&mut self, item: T, priority: P) -> Handle {
+		let position = self.heap.len();
+		let slot = match self.free.pop() {
+			Some(slot) => {
+				self.slots[slot].generation += 1;
+				self.slots[slot].position = Some(position);
+				slot
+			},
+			None => {
+				self.slots.push(Slot {position: Some(position), generation: 0});
+				self.slots.len() - 1
+			},
+		};
+		self.heap.push(HeapEntry {item, priority, slot});
+		self.sift_up(position);
+		Handle {slot, generation: self.slots[slot].generation}
+	}
+	//the item with the highest priority
+	pub fn pop(&mut self) -> Option<(T,P)> {
+		self.remove_at(0)
+	}
+	pub fn peek(&self) -> Option<(&T,&P)> {
+		self.heap.first().map(|entry| (&entry.item,&entry.priority))
+	}
+	pub fn get(&self, handle: Handle) -> Option<(&T,&P)> {
+		let entry = &self.heap[self.position(handle)?];
+		Some( (&entry.item,&entry.priority) )
+	}
+	pub fn contains(&self, handle: Handle) -> bool {
+		self.position(handle).is_some()
+	}
+	//gives back the old priority, None if the item is no longer in the queue
+	pub fn change_priority(&mut self, handle: Handle, priority: P) -> Option<P> {
+		let position = self.position(handle)?;
+		let old = std::mem::replace(&mut self.heap[position].priority,priority);
+		//it only needs to move one way
+		match self.heap[position].priority > old {
+			true => self.sift_up(position),
+			false => self.sift_down(position),
+		}
+		Some(old)
+	}
+	pub fn remove(&mut self, handle: Handle) -> Option<(T,P)> {
+		let position = self.position(handle)?;
+		self.remove_at(position)
+	}
+	pub fn len(&self) -> usize {
+		self.heap.len()
+	}
+	pub fn is_empty(&self) -> bool {
+		self.heap.is_empty()
+	}
+	//====== heap upkeep ======
+	fn position(&self, handle: Handle) -> Option<usize> {
+		let slot = self.slots.get(handle.slot)?;
+		if slot.generation != handle.generation {return None}
+		slot.position
+	}
+	fn remove_at(&mut self, position: usize) -> Option<(T,P)> {
+		if position >= self.heap.len() {return None}
+		//the last item fills the gap, then goes up or down to where it belongs
+		let last = self.heap.len() - 1;
+		self.swap(position,last);
+		let entry = self.heap.pop()?;
+		self.slots[entry.slot].position = None;
+		self.free.push(entry.slot);
+		if position < self.heap.len() {
+			self.sift_up(position);
+			self.sift_down(position);
+		}
+		Some( (entry.item,entry.priority) )
+	}
+	fn swap(&mut self, a: usize, b: usize){
+		self.heap.swap(a,b);
+		self.slots[self.heap[a].slot].position = Some(a);
+		self.slots[self.heap[b].slot].position = Some(b);
+	}
+	fn sift_up(&mut self, mut position: usize){
+		while position > 0 {
+			let parent = (position - 1) / 2;
+			if self.heap[position].priority <= self.heap[parent].priority {break}
+			self.swap(position,parent);
+			position = parent;
+		}
+	}
+	fn sift_down(&mut self, mut position: usize){
+		loop {
+			let mut largest = position;
+			for child in [position * 2 + 1,position * 2 + 2] {
+				if child < self.heap.len() && self.heap[child].priority > self.heap[largest].priority {
+					largest = child;
+				}
+			}
+			if largest == position {break}
+			self.swap(position,largest);
+			position = largest;
+		}
+	}
+}
+impl<T, P: Ord> Default for PriorityQueue<T,P> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+impl<T, P: Ord> FromIterator<(T,P)> for PriorityQueue<T,P> {
+	fn from_iter<I: IntoIterator<Item = (T,P)>>(iter: I) -> Self {
+		let mut queue = Self::new();
+		for (item,priority) in iter {queue.push(item,priority);}
+		queue
+	}
+}
@@ -2,27 +2,34 @@ use target_lexicon::Triple;
 use std::path::Path;
 use std::ffi::OsStr;
 use faerie::artifact::*;
-use args::Args;
+use args::{Parser,OptSpec,Positional,Arity,ValueType,Shell};
 use std::error::Error;
 use std::fs::{File,read};
 
 fn main() -> Result<(),Box<dyn Error>> {
 	//====== get file names from command line ======
-	let args = Args::new(std::env::args().collect::<Vec<_>>()[1..].to_vec(),
-		vec![
-			(Some("h"),Some("help"),false),
-			(Some("o"),Some("output"),true),
-			(Some("n"),Some("name-only"),false),
-		]
-	)?;
-	if args.has("h","help") {
-		print_help();
+	let parser = Parser::new()
+		.usage("[options] <file 1> ... <file n>")
+		.positional(Positional::new("file").value(ValueType::Path).arity(Arity::ZeroOrMore))
+		.opt(OptSpec::new("output").short("o").long("output").value(ValueType::Path).placeholder("name").default("data.o")
+			.help("change the name of the output file"))
+		.opt(OptSpec::new("help").short("h").long("help").help("display this help message"))
+		.opt(OptSpec::new("name-only").short("n").long("name-only")
+			.help("Do not use the full path (\"/usr/bin/a\" etc), only use the filename when generating symbol names (\"/usr/bin/a\" becomes \"a\")"))
+		.opt(OptSpec::new("completion").long("completion").value(ValueType::String).placeholder("shell").help("print a bash, zsh or fish completion script"));
+	let args = parser.parse_env()?;
+	if args.is_present("help") {
+		print!("{}",parser.help());
+		return Ok(());
+	}
+	if let Some(shell) = args.get::<Shell>("completion")? {
+		print!("{}",parser.completion(shell));
 		return Ok(());
 	}
-	let name_only = args.has("n","name-only");
-	let file_name = args.get_arg(Some("o"),Some("output"))
-		.unwrap_or("data.o");
-	let output_file = match File::create(file_name){
+	let name_only = args.is_present("name-only");
+	let file_name = args.get::<String>("output")?
+		.expect("output has a default");
+	let output_file = match File::create(&file_name){
 		Ok(f) => f,
 		Err(e) => {
 			eprintln!("Could not open output file {file_name:?}: {e}");
@@ -48,7 +55,7 @@ fn main() -> Result<(),Box<dyn Error>> {
 		.collect::<Vec<_>>();
 	//====== start building our object file ====== 
 	let mut object = ArtifactBuilder::new(Triple::host())
-		.name(file_name.to_string())
+		.name(file_name)
 		.finish();
 	//====== declare symbols ======
 	//data symbols
@@ -87,12 +94,3 @@ fn sanitise_symbol_name(name: String) -> String {
 		.filter(|c| (c.is_alphanumeric() || *c == '_') && c.is_ascii())
 		.collect()
 }
-
-fn print_help(){
-	let name = std::env::args().next().expect("No argv[0] found");
-	println!("Usage: {name} [options] <file 1> ... <file n>");
-	println!("Options:");
-	println!("	-o, --output <name> : change the name of the output file. defaults to data.o");
-	println!("	-h, --help          : display this help message");
-	println!("	-n, --name-only     : Do not use the full path (\"/usr/bin/a\" etc), only use the filename when generating symbol names (\"/usr/bin/a\" becomes \"a\")");
-}
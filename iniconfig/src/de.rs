@@ -0,0 +1,227 @@
+//======================= serde =======================
+//deserializes a section into a struct with a field for each key, or a whole file
+//into a struct or map with a field for each global key and top level section,
+//with subsections nested inside their parents. Values are only parsed once
+//the type asks for them, so `depth = 3` can go into a usize or a String
+use crate::{ConfigFile,ConfigSection,SectionTree,ValueError,parse_bool};
+use serde::de::{self,DeserializeOwned,DeserializeSeed,Deserializer,IntoDeserializer,MapAccess,Visitor};
+use serde::de::value::{SeqDeserializer,StrDeserializer};
+use std::fmt::{Display,Formatter};
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct DeError(String);
+impl de::Error for DeError {
+	fn custom<T: Display>(message: T) -> Self {
+		DeError(message.to_string())
+	}
+}
+impl Display for DeError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error>{
+		write!(f,"{}",self.0)
+	}
+}
+impl std::error::Error for DeError {}
+
+impl ConfigSection {
+	// #[derive(Deserialize)]
+	// struct Session {command: String, cwd: Option<PathBuf>, restart: bool}
+	// let session = section.deserialize::<Session>()?;
+	pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T,DeError> {
+		T::deserialize(SectionDeserializer(self))
+	}
+}
+impl ConfigFile {
+	//e.g. `config.deserialize::<HashMap<String,Session>>()?`
+	pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T,DeError> {
+		T::deserialize(TreeDeserializer(&self.tree()))
+	}
+}
+//====== whole files ======
+//the keys of the section followed by its subsections
+struct TreeDeserializer<'a,'b>(&'b SectionTree<'a>);
+impl<'de> Deserializer<'de> for TreeDeserializer<'_,'_> {
+	type Error = DeError;
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value,DeError> {
+		let keys = self.0.section().map(|section| section_entries(section)).unwrap_or_default();
+		let keys = keys.into_iter().map(|(key,value)| (key,Entry::Value(value)));
+		let children = self.0.children().iter().map(|child| (child.name(),Entry::Tree(TreeDeserializer(child))));
+		visitor.visit_map(Entries {entries: keys.chain(children), value: None})
+	}
+	serde::forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf option unit unit_struct newtype_struct seq tuple
+		tuple_struct map struct enum identifier ignored_any
+	}
+}
+//====== sections ======
+struct SectionDeserializer<'a>(&'a ConfigSection);
+impl<'de> Deserializer<'de> for SectionDeserializer<'_> {
+	type Error = DeError;
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value,DeError> {
+		visitor.visit_map(Entries {entries: section_entries(self.0).into_iter(), value: None})
+	}
+	serde::forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf option unit unit_struct newtype_struct seq tuple
+		tuple_struct map struct enum identifier ignored_any
+	}
+}
+//each key once, in the order it first appears
+fn section_entries(section: &ConfigSection) -> Vec<(&str,ValueDeserializer<'_>)> {
+	let mut keys: Vec<&str> = vec![];
+	for (key,_) in &section.entries {
+		if !keys.contains(&key.as_str()) {keys.push(key)}
+	}
+	keys.into_iter()
+		.map(|key| (key,ValueDeserializer {section: section.name(), key, values: section.get_all(key)}))
+		.collect()
+}
+//a file has both keys and sections at the same level
+enum Entry<'a,'b> {
+	Value(ValueDeserializer<'a>),
+	Tree(TreeDeserializer<'a,'b>),
+}
+//values know how to parse themselves, sections are always maps
+macro_rules! delegate {
+	($($method:ident)*) => {$(
+		fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value,DeError> {
+			match self {
+				Entry::Value(value) => value.$method(visitor),
+				Entry::Tree(tree) => tree.deserialize_any(visitor),
+			}
+		}
+	)*};
+}
+impl<'de> Deserializer<'de> for Entry<'_,'_> {
+	type Error = DeError;
+	delegate! {
+		deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+		deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+		deserialize_f32 deserialize_f64 deserialize_char deserialize_seq deserialize_unit
+	}
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value,DeError> {
+		visitor.visit_some(self)
+	}
+	fn deserialize_enum<V: Visitor<'de>>(self, name: &'static str, variants: &'static [&'static str], visitor: V) -> Result<V::Value,DeError> {
+		match self {
+			Entry::Value(value) => value.deserialize_enum(name,variants,visitor),
+			Entry::Tree(tree) => tree.deserialize_any(visitor),
+		}
+	}
+	fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value,DeError> {
+		visitor.visit_newtype_struct(self)
+	}
+	serde::forward_to_deserialize_any! {
+		str string bytes byte_buf unit_struct tuple tuple_struct map struct identifier ignored_any
+	}
+}
+//name and deserializer pairs, for the sections in a file or the keys in a section
+struct Entries<I,D> {
+	entries: I,
+	value: Option<D>,
+}
+impl<'de,'a,I,D> MapAccess<'de> for Entries<I,D>
+where I: Iterator<Item = (&'a str,D)>, D: Deserializer<'de,Error = DeError> {
+	type Error = DeError;
+	fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>,DeError> {
+		let Some((name,value)) = self.entries.next() else {return Ok(None)};
+		self.value = Some(value);
+		seed.deserialize(StrDeserializer::<DeError>::new(name)).map(Some)
+	}
+	fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value,DeError> {
+		match self.value.take() {
+			Some(value) => seed.deserialize(value),
+			None => Err(de::Error::custom("value asked for before its key")),
+		}
+	}
+}
+//====== values ======
+//every value given to a key, the last one is used unless a list is asked for
+struct ValueDeserializer<'a> {
+	section: &'a str,
+	key: &'a str,
+	values: Vec<&'a str>,
+}
+impl ValueDeserializer<'_> {
+	fn value(&self) -> &str {
+		self.values.last().copied().unwrap_or("")
+	}
+	fn invalid(&self) -> DeError {
+		let error = ValueError {section: self.section.into(), key: self.key.into(), value: self.value().into()};
+		DeError(error.to_string())
+	}
+}
+//lets list items go through SeqDeserializer
+impl<'de,'a> IntoDeserializer<'de,DeError> for ValueDeserializer<'a> {
+	type Deserializer = Self;
+	fn into_deserializer(self) -> Self {
+		self
+	}
+}
+macro_rules! parse_value {
+	($($method:ident => $visit:ident,)*) => {$(
+		fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value,DeError> {
+			match self.value().trim().parse() {
+				Ok(value) => visitor.$visit(value),
+				Err(_) => Err(self.invalid()),
+			}
+		}
+	)*};
+}
+impl<'de> Deserializer<'de> for ValueDeserializer<'_> {
+	type Error = DeError;
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value,DeError> {
+		visitor.visit_str(self.value())
+	}
+	fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value,DeError> {
+		match parse_bool(self.value()) {
+			Some(value) => visitor.visit_bool(value),
+			None => Err(self.invalid()),
+		}
+	}
+	parse_value! {
+		deserialize_i8 => visit_i8,
+		deserialize_i16 => visit_i16,
+		deserialize_i32 => visit_i32,
+		deserialize_i64 => visit_i64,
+		deserialize_i128 => visit_i128,
+		deserialize_u8 => visit_u8,
+		deserialize_u16 => visit_u16,
+		deserialize_u32 => visit_u32,
+		deserialize_u64 => visit_u64,
+		deserialize_u128 => visit_u128,
+		deserialize_f32 => visit_f32,
+		deserialize_f64 => visit_f64,
+		deserialize_char => visit_char,
+	}
+	//the key being there at all means Some
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value,DeError> {
+		visitor.visit_some(self)
+	}
+	//comma separated and/or repeated keys, like ConfigSection::get_list
+	fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value,DeError> {
+		let (section,key) = (self.section,self.key);
+		let items = self.values.into_iter()
+			.flat_map(|value| value.split(','))
+			.map(str::trim)
+			.filter(|item| !item.is_empty())
+			.map(|item| ValueDeserializer {section, key, values: vec![item]});
+		let mut seq = SeqDeserializer::new(items);
+		let value = visitor.visit_seq(&mut seq)?;
+		seq.end()?;
+		Ok(value)
+	}
+	//unit variants only, e.g. `restart = always` into `enum Restart {Always, Never}`
+	fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value,DeError> {
+		visitor.visit_enum(StrDeserializer::new(self.value()))
+	}
+	fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value,DeError> {
+		visitor.visit_newtype_struct(self)
+	}
+	fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value,DeError> {
+		visitor.visit_unit()
+	}
+	serde::forward_to_deserialize_any! {
+		str string bytes byte_buf unit_struct tuple tuple_struct map struct identifier ignored_any
+	}
+}
@@ -0,0 +1,210 @@
+//======================= round trip documents =======================
+//keeps every line as it was so the file can be edited and written back
+//with only the changed lines different
+use crate::{ConfigFile,IniError,crop_comments,continues,logical_lines,is_label,extract_label,is_property,parse_value,quote_value,section_path};
+use std::fmt::{Display,Formatter};
+use std::io::Write;
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct Document {
+	lines: Vec<Line>,
+	newline: String,
+	//whether the last line ends with a newline
+	trailing_newline: bool,
+}
+#[derive(Debug,Clone,PartialEq)]
+enum Line {
+	//blank lines, comments and anything that does not parse
+	Other(String),
+	Section {raw: String, name: String},
+	//raw has a "\n" between lines if it was continued with a backslash
+	//the ranges are byte offsets into raw, so the value can be replaced without touching the rest
+	//and value is what it means once the quotes and escapes are taken out
+	Property {raw: String, key: (usize,usize), range: (usize,usize), value: String},
+}
+impl Line {
+	fn parse(raw: &str) -> Self {
+		let content = logical_lines(raw).into_iter().next().map(|(_,content)| content).unwrap_or_default();
+		if is_label(&content) {
+			return Line::Section {raw: raw.into(), name: extract_label(&content)};
+		}
+		if !is_property(&content) {return Line::Other(raw.into())}
+		//====== find the key and value in the first line ======
+		let first = crop_comments(raw.split('\n').next().unwrap_or(""));
+		let equals = first.find('=').unwrap_or(0);
+		let key_start = first.len() - first.trim_start().len();
+		let key_end = first[..equals].trim_end().len();
+		let value_start = first.len() - first[equals + 1..].trim_start().len();
+		//====== and the end of the value in the last line ======
+		let last_start = raw.rfind('\n').map_or(0,|newline| newline + 1);
+		let value_end = (last_start + crop_comments(&raw[last_start..]).trim_end().len()).max(value_start);
+		let (_,raw_value) = content.split_once('=').unwrap_or_default();
+		let value = parse_value(raw_value).unwrap_or_else(|_| raw_value.trim().into());
+		Line::Property {raw: raw.into(), key: (key_start,key_end), range: (value_start,value_end), value}
+	}
+	fn raw(&self) -> &str {
+		match self {
+			Line::Other(raw) | Line::Section {raw,..} | Line::Property {raw,..} => raw,
+		}
+	}
+	fn key(&self) -> Option<&str> {
+		match self {
+			Line::Property {raw,key,..} => Some(&raw[key.0..key.1]),
+			_ => None,
+		}
+	}
+	fn value(&self) -> Option<&str> {
+		match self {
+			Line::Property {value,..} => Some(value),
+			_ => None,
+		}
+	}
+	//swaps the value, keeping the spacing and any comment after it
+	//a value continued over several lines ends up on one
+	fn set_value(&mut self, new_value: &str) {
+		if let Line::Property {raw,range,value,..} = self {
+			let quoted = quote_value(new_value);
+			raw.replace_range(range.0..range.1,&quoted);
+			range.1 = range.0 + quoted.len();
+			*value = new_value.into();
+		}
+	}
+}
+impl Document {
+	pub fn new() -> Self {
+		Self {lines: vec![], newline: "\n".into(), trailing_newline: true}
+	}
+	//the same checks as ConfigFile::parse
+	pub fn parse(data: &str) -> Result<Self,IniError> {
+		ConfigFile::parse(data)?;
+		Ok(Self::from(data))
+	}
+	//====== reading ======
+	//section names in the order they appear
+	pub fn sections(&self) -> impl Iterator<Item = &str> {
+		self.lines.iter().filter_map(|line| match line {
+			Line::Section {name,..} => Some(name.as_str()),
+			_ => None,
+		})
+	}
+	pub fn has_section(&self, section: &str) -> bool {
+		self.section_range(section).is_some()
+	}
+	//if the key is given more than once the last one wins, like ConfigFile
+	pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+		let (start,end) = self.section_range(section)?;
+		self.lines[start..end].iter()
+			.rfind(|line| line.key() == Some(key))
+			.and_then(Line::value)
+	}
+	//keys and values in the order they appear
+	pub fn properties(&self, section: &str) -> Vec<(&str,&str)> {
+		let Some((start,end)) = self.section_range(section) else {return vec![]};
+		self.lines[start..end].iter()
+			.filter_map(|line| Some( (line.key()?,line.value()?) ))
+			.collect()
+	}
+	//====== editing ======
+	//changes the value in place if the key exists, otherwise adds it after the last
+	//property in the section, adding the section to the end if it does not exist
+	pub fn set(&mut self, section: &str, key: &str, value: &str) {
+		if !self.has_section(section) {self.add_section(section);}
+		let (start,end) = self.section_range(section).unwrap();
+		if let Some(line) = self.lines[start..end].iter_mut().rfind(|line| line.key() == Some(key)) {
+			line.set_value(value);
+			return;
+		}
+		//====== new property, formatted like the one before it ======
+		let previous = self.lines[start..end].iter().rposition(|line| line.key().is_some());
+		let value = quote_value(value);
+		let raw = match previous.map(|position| &self.lines[start + position]) {
+			Some(Line::Property {raw,key: (key_start,key_end),range: (value_start,_),..}) =>
+				format!("{}{key}{}{value}",&raw[..*key_start],&raw[*key_end..*value_start]),
+			_ => format!("{key} = {value}"),
+		};
+		let index = match previous {
+			Some(position) => start + position + 1,
+			None => start,
+		};
+		self.lines.insert(index,Line::parse(&raw));
+	}
+	//removes every line setting the key, returning the value that was used
+	pub fn remove(&mut self, section: &str, key: &str) -> Option<String> {
+		let (start,end) = self.section_range(section)?;
+		let value = self.get(section,key).map(String::from);
+		for index in (start..end).rev() {
+			if self.lines[index].key() == Some(key) {self.lines.remove(index);}
+		}
+		value
+	}
+	//adds an empty section to the end, returns false if it already exists
+	pub fn add_section(&mut self, section: &str) -> bool {
+		if section.is_empty() || self.has_section(section) {return false}
+		if self.lines.last().is_some_and(|line| !line.raw().trim().is_empty()) {
+			self.lines.push(Line::Other(String::new()));
+		}
+		self.lines.push(Line::parse(&format!("[{section}]")));
+		true
+	}
+	pub fn write_to(&self, mut writer: impl Write) -> std::io::Result<()> {
+		write!(writer,"{self}")
+	}
+	//the lines after the first header with that name, up to the next header
+	//"" is the global section before the first header
+	fn section_range(&self, section: &str) -> Option<(usize,usize)> {
+		let start = match section.is_empty() {
+			true => 0,
+			false => 1 + self.lines.iter().position(|line| match line {
+				Line::Section {name,..} => name == section || section_path(name).is_some_and(|path| path.join(".") == section),
+				_ => false,
+			})?,
+		};
+		let end = self.lines[start..].iter()
+			.position(|line| matches!(line,Line::Section {..}))
+			.map_or(self.lines.len(),|position| start + position);
+		Some( (start,end) )
+	}
+}
+impl Default for Document {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+//never fails, lines that do not parse are kept as they are
+impl From<&str> for Document {
+	fn from(data: &str) -> Self {
+		let newline = if data.contains("\r\n") {"\r\n"} else {"\n"};
+		let trailing_newline = data.is_empty() || data.ends_with('\n');
+		let data = data.strip_suffix('\n').unwrap_or(data);
+		//====== group continued lines together ======
+		let mut lines = vec![];
+		let mut raw = String::new();
+		for line in data.split('\n').filter(|_| !data.is_empty()) {
+			let line = line.strip_suffix('\r').unwrap_or(line);
+			raw.push_str(line);
+			if continues(crop_comments(line)) {
+				raw.push('\n');
+				continue;
+			}
+			lines.push(Line::parse(&raw));
+			raw.clear();
+		}
+		//continued past the end of the file
+		if !raw.is_empty() {lines.push(Line::parse(raw.trim_end_matches('\n')))}
+		Self {lines, newline: newline.into(), trailing_newline}
+	}
+}
+impl Display for Document {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error>{
+		for (index,line) in self.lines.iter().enumerate() {
+			write!(f,"{}",line.raw().replace('\n',&self.newline))?;
+			if index + 1 < self.lines.len() || self.trailing_newline {write!(f,"{}",self.newline)?}
+		}
+		Ok(())
+	}
+}
+impl From<&Document> for ConfigFile {
+	fn from(document: &Document) -> Self {
+		ConfigFile::from(document.to_string().as_str())
+	}
+}
@@ -0,0 +1,136 @@
+//======================= interpolation =======================
+// cwd = ${env:HOME}/servers/${name}
+// log = ${paths:logs}/mc.log
+//`${key}` looks in the same section then the global one, `${section:key}` in the given
+//section (the one it is in if that has the name) and `${env:VAR}` in the environment. `$${` gives a literal `${`
+use crate::{ConfigFile,ConfigSection,Origin};
+use std::fmt::{Display,Formatter};
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct InterpolateError {
+	//the property the reference is in
+	pub section: String,
+	pub key: String,
+	pub origin: Option<Origin>,
+	pub kind: InterpolateErrorKind,
+}
+#[derive(Debug,Clone,PartialEq)]
+pub enum InterpolateErrorKind {
+	//the reference as written, e.g. "paths:logs"
+	Undefined(String),
+	//the references that lead back to the start, e.g. ["a:x","a:y","a:x"]
+	Cycle(Vec<String>),
+	//"${" without the "}"
+	Unterminated,
+}
+//e.g. "line 3: cwd in [mc]: ${env:NOPE} is not defined"
+impl Display for InterpolateError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error>{
+		if let Some(origin) = &self.origin {write!(f,"{origin}: ")?}
+		write!(f,"{} in [{}]: ",self.key,self.section)?;
+		match &self.kind {
+			InterpolateErrorKind::Undefined(reference) => write!(f,"${{{reference}}} is not defined"),
+			InterpolateErrorKind::Cycle(references) => write!(f,"references loop back on themselves: {}",references.join(" -> ")),
+			InterpolateErrorKind::Unterminated => write!(f,"missing \"}}\" after \"${{\""),
+		}
+	}
+}
+impl std::error::Error for InterpolateError {}
+
+impl ConfigFile {
+	//expands the references in every value, references are followed so they can use each other
+	pub fn interpolate(&mut self) -> Result<(),InterpolateError> {
+		let global = expand_section(self,&self.global)?;
+		let sections = self.sections.iter()
+			.map(|section| expand_section(self,section))
+			.collect::<Result<Vec<_>,_>>()?;
+		set_entries(&mut self.global,global);
+		for (section,entries) in self.sections.iter_mut().zip(sections) {
+			set_entries(section,entries);
+		}
+		Ok(())
+	}
+	//a single value with its references expanded, the file is left as it is
+	pub fn get_interpolated(&self, section: &str, key: &str) -> Result<Option<String>,InterpolateError> {
+		let Some(section) = self.get_section(section) else {return Ok(None)};
+		let Some(value) = section.get_str(key) else {return Ok(None)};
+		expand(self,section,key,value,&mut vec![(section,key)]).map(Some)
+	}
+}
+fn expand_section(config: &ConfigFile, section: &ConfigSection) -> Result<Vec<(String,String)>,InterpolateError> {
+	section.entries.iter()
+		.map(|(key,value)| {
+			let value = expand(config,section,key,value,&mut vec![(section,key.as_str())])?;
+			Ok( (key.clone(),value) )
+		})
+		.collect()
+}
+fn set_entries(section: &mut ConfigSection, entries: Vec<(String,String)>) {
+	for (key,value) in &entries {
+		section.properties.insert(key.clone(),value.clone());
+	}
+	section.entries = entries;
+}
+//"section:key", or just "key" for the global section
+fn reference_name(section: &ConfigSection, key: &str) -> String {
+	match section.name.is_empty() {
+		true => key.into(),
+		false => format!("{}:{key}",section.name),
+	}
+}
+//stack is the properties being expanded, to catch cycles. They are compared by section
+//rather than name since DuplicateSections::KeepAll can give several sections the same one
+fn expand<'a>(config: &'a ConfigFile, section: &'a ConfigSection, key: &str, value: &'a str, stack: &mut Vec<(&'a ConfigSection,&'a str)>) -> Result<String,InterpolateError> {
+	let error = |kind| InterpolateError {
+		section: section.name.clone(),
+		key: key.into(),
+		origin: section.key_origin(key).cloned(),
+		kind,
+	};
+	let mut result = String::new();
+	let mut rest = value;
+	while let Some(start) = rest.find("${") {
+		//====== "$${" is a literal "${" ======
+		if rest[..start].ends_with('$') {
+			result.push_str(&rest[..start - 1]);
+			result.push_str("${");
+			rest = &rest[start + 2..];
+			continue;
+		}
+		result.push_str(&rest[..start]);
+		let Some(end) = rest[start..].find('}') else {return Err(error(InterpolateErrorKind::Unterminated))};
+		let reference = &rest[start + 2..start + end];
+		rest = &rest[start + end + 1..];
+		//====== environment variables ======
+		if let Some(var) = reference.strip_prefix("env:") {
+			let value = std::env::var(var)
+				.map_err(|_| error(InterpolateErrorKind::Undefined(reference.into())))?;
+			result.push_str(&value);
+			continue;
+		}
+		//====== other properties ======
+		//a section's own name means itself before any other section with that name
+		let target = match reference.rsplit_once(':') {
+			Some((name,key)) => std::iter::once(section).filter(|own| own.is_named(name)).chain(config.get_section(name))
+				.find(|target| target.get_str(key).is_some())
+				.map(|target| (target,key)),
+			None => [section,&config.global].into_iter()
+				.find(|target| target.get_str(reference).is_some())
+				.map(|target| (target,reference)),
+		};
+		let Some((target,target_key)) = target else {
+			return Err(error(InterpolateErrorKind::Undefined(reference.into())));
+		};
+		if stack.iter().any(|&(other,other_key)| std::ptr::eq(other,target) && other_key == target_key) {
+			let mut references = stack.iter().map(|&(other,other_key)| reference_name(other,other_key)).collect::<Vec<_>>();
+			references.push(reference_name(target,target_key));
+			return Err(error(InterpolateErrorKind::Cycle(references)));
+		}
+		stack.push((target,target_key));
+		let value = expand(config,target,target_key,target.get_str(target_key).unwrap_or(""),stack)?;
+		stack.pop();
+		result.push_str(&value);
+	}
+	result.push_str(rest);
+	Ok(result)
+}
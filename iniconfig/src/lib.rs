@@ -1,92 +1,1099 @@
-#![feature(trim_prefix_suffix)]
-#![allow(dead_code)]
+mod document;
+pub use document::Document;
+mod load;
+pub use load::{LoadError,LoadErrorKind};
+mod interpolate;
+pub use interpolate::{InterpolateError,InterpolateErrorKind};
+mod schema;
+pub use schema::{Schema,SectionSchema,KeySchema,ValueType,SchemaError,SchemaErrorKind,edit_distance};
+mod reader;
+pub use reader::{Reader,Event,Span};
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "serde")]
+pub use de::DeError;
 
 use std::collections::HashMap;
+use std::fmt::{Display,Formatter};
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::str::FromStr;
 
-#[derive(Debug,Clone)]
+#[derive(Debug,Clone,PartialEq)]
 pub struct ConfigFile {
+	//properties before the first section header
+	global: ConfigSection,
 	sections: Vec<ConfigSection>,
 }
-#[derive(Debug,Clone)]
+#[derive(Debug,Clone,PartialEq)]
 pub struct ConfigSection {
 	name: String,
+	//["parent","child"] for [parent.child] or [parent "child"]
+	path: Vec<String>,
 	properties: HashMap<String,String>,
+	//every property in order, including repeated keys
+	entries: Vec<(String,String)>,
+	//where the header was, None for the global section
+	origin: Option<Origin>,
+	//where the value of each key came from
+	origins: HashMap<String,Origin>,
+}
+//where a section header or property is
+#[derive(Debug,Clone,PartialEq,Default)]
+pub struct Origin {
+	//None if it was not loaded from a file
+	pub file: Option<PathBuf>,
+	pub line: usize,
+}
+//"config.ini:3" or "line 3"
+impl Display for Origin {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error>{
+		match &self.file {
+			Some(file) => write!(f,"{}:{}",file.display(),self.line),
+			None => write!(f,"line {}",self.line),
+		}
+	}
 }
 
 impl ConfigSection {
-	pub fn name<'a>(&'a self) -> &'a str {
+	fn new(name: &str) -> Self {
+		let path = match name.is_empty() {
+			true => vec![],
+			false => section_path(name).unwrap_or_else(|| vec![name.into()]),
+		};
+		Self {name: name.into(), path, properties: HashMap::new(), entries: vec![], origin: None, origins: HashMap::new()}
+	}
+	//either the name as written or the path joined with dots
+	fn is_named(&self, name: &str) -> bool {
+		self.name == name || self.path.join(".") == name
+	}
+	fn insert(&mut self, key: String, value: String, origin: Origin) {
+		self.entries.push( (key.clone(),value.clone()) );
+		self.origins.insert(key.clone(),origin);
+		self.properties.insert(key,value);
+	}
+	//like insert, but throws away any earlier values so it is not added to a list
+	fn replace(&mut self, key: String, value: String, origin: Origin) {
+		self.entries.retain(|(entry_key,_)| *entry_key != key);
+		self.insert(key,value,origin);
+	}
+	//adds the other section's properties, its values win
+	fn merge(&mut self, other: ConfigSection) {
+		for key in other.properties.keys() {
+			self.entries.retain(|(entry_key,_)| entry_key != key);
+		}
+		for (key,value) in other.entries {
+			let origin = other.origins.get(&key).cloned().unwrap_or_default();
+			self.insert(key,value,origin);
+		}
+	}
+	//as written in the header, e.g. `parent "child"`
+	pub fn name(&self) -> &str {
 		&self.name
 	}
-	pub fn properties<'a>(&'a self) -> &'a HashMap<String,String> {
+	pub fn path(&self) -> &[String] {
+		&self.path
+	}
+	//where the header is, None for the global section
+	pub fn origin(&self) -> Option<&Origin> {
+		self.origin.as_ref()
+	}
+	//where the value get_str gives came from
+	pub fn key_origin(&self, key: &str) -> Option<&Origin> {
+		self.origins.get(key)
+	}
+	pub fn properties(&self) -> &HashMap<String,String> {
 		&self.properties
 	}
+	//====== typed access ======
+	pub fn get_str(&self, key: &str) -> Option<&str> {
+		self.properties.get(key).map(String::as_str)
+	}
+	//e.g. `section.get::<usize>("depth")?.unwrap_or(3)`
+	//Ok(None) if the key is not there
+	pub fn get<T: FromStr>(&self, key: &str) -> Result<Option<T>,ValueError> {
+		let Some(value) = self.get_str(key) else {return Ok(None)};
+		match value.parse::<T>() {
+			Ok(value) => Ok(Some(value)),
+			Err(_) => Err(self.value_error(key,value)),
+		}
+	}
+	//accepts yes/no, true/false, on/off and 1/0 in any case
+	pub fn get_bool(&self, key: &str) -> Result<Option<bool>,ValueError> {
+		let Some(value) = self.get_str(key) else {return Ok(None)};
+		match parse_bool(value) {
+			Some(value) => Ok(Some(value)),
+			None => Err(self.value_error(key,value)),
+		}
+	}
+	//every value given to the key in order, for when it is repeated
+	pub fn get_all(&self, key: &str) -> Vec<&str> {
+		self.entries.iter()
+			.filter(|(entry_key,_)| entry_key == key)
+			.map(|(_,value)| value.as_str())
+			.collect()
+	}
+	//"a, b, c" gives ["a","b","c"], repeating the key adds to the list
+	//only ConfigFile::from allows repeated keys, ConfigFile::parse treats them as an error
+	pub fn get_list(&self, key: &str) -> Vec<&str> {
+		self.get_all(key).into_iter()
+			.flat_map(|value| value.split(','))
+			.map(str::trim)
+			.filter(|item| !item.is_empty())
+			.collect()
+	}
+	fn value_error(&self, key: &str, value: &str) -> ValueError {
+		ValueError {section: self.name.clone(), key: key.into(), value: value.into()}
+	}
+}
+pub fn parse_bool(value: &str) -> Option<bool> {
+	match value.trim().to_lowercase().as_str() {
+		"yes" | "true" | "on" | "1" => Some(true),
+		"no" | "false" | "off" | "0" => Some(false),
+		_ => None,
+	}
 }
 impl ConfigFile {
 	fn new() -> Self {
-		Self { sections: vec![] }
+		Self { global: ConfigSection::new(""), sections: vec![] }
+	}
+	//unlike From, anything that is not a section header, property, comment or blank line is an error
+	//as are repeated sections or keys
+	pub fn parse(data: &str) -> Result<Self,IniError> {
+		Self::parse_with(data,&ParseOptions::default())
+	}
+	pub fn parse_with(data: &str, options: &ParseOptions) -> Result<Self,IniError> {
+		Self::read_with(data.as_bytes(),options)
+	}
+	//parse, but a line at a time from a file, socket etc.
+	pub fn read(input: impl BufRead) -> Result<Self,IniError> {
+		Self::read_with(input,&ParseOptions::default())
+	}
+	pub fn read_with(input: impl BufRead, options: &ParseOptions) -> Result<Self,IniError> {
+		let mut config = Self::new();
+		let mut parser = FileParser::new(options,None);
+		for event in options.reader(input) {
+			parser.event(&mut config,event?)?;
+		}
+		Ok(config)
+	}
+	//====== lookup ======
+	//the properties before the first section header, with an empty name
+	pub fn global(&self) -> &ConfigSection {
+		&self.global
+	}
+	//the first section with that name, subsections can be found with either
+	//`parent.child` or `parent "child"`, "" gives the global section
+	pub fn get_section(&self, name: &str) -> Option<&ConfigSection> {
+		if name.is_empty() {return Some(&self.global)}
+		self.sections.iter().find(|section| section.is_named(name))
+	}
+	//every section with that name, for files parsed with DuplicateSections::KeepAll
+	pub fn sections_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a ConfigSection> {
+		self.sections.iter().filter(move |section| section.is_named(name))
+	}
+	//e.g. `config.get_path(&["remote","origin"])` for [remote "origin"]
+	pub fn get_path(&self, path: &[&str]) -> Option<&ConfigSection> {
+		self.sections.iter().find(|section| section.path == path)
+	}
+	//the sections arranged by their paths, with the global section at the root
+	//sections that are only implied, like [a] for [a.b], have no properties
+	pub fn tree(&self) -> SectionTree<'_> {
+		let mut root = SectionTree {name: "", section: Some(&self.global), children: vec![]};
+		for section in &self.sections {
+			let mut node = &mut root;
+			for name in &section.path {
+				let index = match node.children.iter().position(|child| child.name == name) {
+					Some(index) => index,
+					None => {
+						node.children.push(SectionTree {name, section: None, children: vec![]});
+						node.children.len() - 1
+					},
+				};
+				node = &mut node.children[index];
+			}
+			//the first one wins if there are duplicates
+			if node.section.is_none() {node.section = Some(section)}
+		}
+		root
+	}
+	//e.g. `config.get("mc server","command")`
+	pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+		self.get_section(section)?.get_str(key)
+	}
+	pub fn iter(&self) -> std::slice::Iter<'_,ConfigSection> {
+		self.sections.iter()
+	}
+}
+#[derive(Debug,Clone,PartialEq)]
+pub struct SectionTree<'a> {
+	name: &'a str,
+	section: Option<&'a ConfigSection>,
+	children: Vec<SectionTree<'a>>,
+}
+impl<'a> SectionTree<'a> {
+	//the last part of the path, "child" for [parent.child]
+	pub fn name(&self) -> &'a str {
+		self.name
+	}
+	//None if no header for this exact path was given
+	pub fn section(&self) -> Option<&'a ConfigSection> {
+		self.section
+	}
+	pub fn children(&self) -> &[SectionTree<'a>] {
+		&self.children
+	}
+	pub fn child(&self, name: &str) -> Option<&SectionTree<'a>> {
+		self.children.iter().find(|child| child.name == name)
+	}
+	pub fn get(&self, key: &str) -> Option<&'a str> {
+		self.section?.get_str(key)
+	}
+}
+//======================= parsing =======================
+//the state of parsing one file, the files it includes get their own
+//but they all add to the same ConfigFile
+struct FileParser<'a> {
+	options: &'a ParseOptions,
+	file: Option<PathBuf>,
+	//the section properties are going into, None for the global section
+	current: Option<usize>,
+	//the keys set since the last header
+	keys: Vec<String>,
+	//the paths of the headers in this file, sections from other files are always merged into
+	headers: Vec<Vec<String>>,
+}
+impl<'a> FileParser<'a> {
+	fn new(options: &'a ParseOptions, file: Option<PathBuf>) -> Self {
+		Self {options, file, current: None, keys: vec![], headers: vec![]}
+	}
+	//take over the section another parser was in, for reading a file as if pasted into another
+	fn continue_from(&mut self, other: &mut FileParser) {
+		self.current = other.current;
+		self.keys = std::mem::take(&mut other.keys);
+	}
+	fn event(&mut self, config: &mut ConfigFile, event: Event) -> Result<(),IniError> {
+		let span = *event.span();
+		let error = |kind| IniError {line: span.line, column: span.column, kind};
+		let origin = Origin {file: self.file.clone(), line: span.line};
+		match event {
+			//====== section header ======
+			Event::Section {name,..} => {
+				self.keys.clear();
+				//[a.b] and [a "b"] are the same section
+				let path = section_path(&name).unwrap_or_default();
+				let existing = config.sections.iter().position(|section| section.path == path);
+				let repeated = self.headers.contains(&path);
+				self.headers.push(path);
+				match (existing,self.options.duplicate_sections) {
+					(Some(_),DuplicateSections::Error) if repeated => {
+						return Err(error(IniErrorKind::DuplicateSection(name)));
+					},
+					(Some(index),DuplicateSections::KeepLast) if repeated => {config.sections.remove(index);},
+					(Some(_),DuplicateSections::KeepAll) if repeated => (),
+					//sections from other files are always merged
+					(Some(index),_) => {
+						self.current = Some(index);
+						return Ok(());
+					},
+					(None,_) => (),
+				}
+				let mut section = ConfigSection::new(&name);
+				section.origin = Some(origin);
+				config.sections.push(section);
+				self.current = Some(config.sections.len() - 1);
+			},
+			//====== property ======
+			Event::Property {key,value,..} => {
+				let section = match self.current {
+					Some(index) => &mut config.sections[index],
+					None => &mut config.global,
+				};
+				if self.options.lenient {
+					section.insert(key,value,origin);
+					return Ok(());
+				}
+				//merged sections can override each other, but not themselves
+				if self.keys.contains(&key) {
+					return Err(error(IniErrorKind::DuplicateKey(key)));
+				}
+				self.keys.push(key.clone());
+				section.replace(key,value,origin);
+			},
+			Event::Comment {..} => (),
+			//only ConfigFile::load knows where to look for the file, lenient skips it like ConfigFile::from
+			Event::Include {..} if self.options.lenient => (),
+			Event::Include {..} => return Err(error(IniErrorKind::BadLine)),
+		}
+		Ok(())
+	}
+}
+//what to do when a section header is used more than once
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum DuplicateSections {
+	//add the properties to the first one, later values win
+	Merge,
+	//throw away the earlier ones
+	KeepLast,
+	//keep them as separate sections, like ConfigFile::from
+	KeepAll,
+	//an IniErrorKind::DuplicateSection error
+	Error,
+}
+//for ConfigFile::parse_with
+// ParseOptions::new().duplicate_sections(DuplicateSections::Merge)
+#[derive(Debug,Clone,PartialEq)]
+pub struct ParseOptions {
+	duplicate_sections: DuplicateSections,
+	lenient: bool,
+}
+impl ParseOptions {
+	pub fn new() -> Self {
+		Self {duplicate_sections: DuplicateSections::Error, lenient: false}
 	}
+	pub fn duplicate_sections(mut self, policy: DuplicateSections) -> Self {
+		self.duplicate_sections = policy;
+		self
+	}
+	//like ConfigFile::from, lines that do not make sense are skipped and repeated keys are added
+	//rather than being an error. With DuplicateSections::KeepAll it reads files the same way
+	pub fn lenient(mut self) -> Self {
+		self.lenient = true;
+		self
+	}
+	fn reader<R: BufRead>(&self, input: R) -> Reader<R> {
+		match self.lenient {
+			true => Reader::new(input).lenient(),
+			false => Reader::new(input),
+		}
+	}
+}
+impl Default for ParseOptions {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+//======================= errors =======================
+#[derive(Debug,Clone,PartialEq)]
+pub struct IniError {
+	pub line: usize,
+	pub column: usize,
+	pub kind: IniErrorKind,
+}
+#[derive(Debug,Clone,PartialEq)]
+pub enum IniErrorKind {
+	//"[section" without the "]", "[]" or anything after the "]"
+	BadHeader,
+	//"= value"
+	MissingKey,
+	//not a header, property or comment
+	BadLine,
+	DuplicateSection(String),
+	//the key was already set in the same section
+	DuplicateKey(String),
+	//a quoted value with no closing quote
+	UnterminatedQuote,
+	//a backslash followed by something that can not be escaped in a double quoted value
+	BadEscape(char),
+	//"key = "value" more"
+	TextAfterQuote,
+	//the input could not be read, e.g. it is not UTF-8
+	Io(std::io::ErrorKind),
+}
+//e.g. "line 3, column 5: unterminated section header"
+impl Display for IniError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error>{
+		write!(f,"line {}, column {}: {}",self.line,self.column,self.kind)
+	}
+}
+impl Display for IniErrorKind {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error>{
+		match self {
+			IniErrorKind::BadHeader => write!(f,"bad section header"),
+			IniErrorKind::MissingKey => write!(f,"property has no key"),
+			IniErrorKind::BadLine => write!(f,"expected a section header or property"),
+			IniErrorKind::DuplicateSection(name) => write!(f,"section [{name}] is already defined"),
+			IniErrorKind::DuplicateKey(key) => write!(f,"key {key:?} is already defined in this section"),
+			IniErrorKind::UnterminatedQuote => write!(f,"missing closing quote"),
+			IniErrorKind::BadEscape(c) => write!(f,"unknown escape \\{c}"),
+			IniErrorKind::TextAfterQuote => write!(f,"unexpected text after closing quote"),
+			IniErrorKind::Io(kind) => write!(f,"could not read: {kind}"),
+		}
+	}
+}
+impl std::error::Error for IniError {}
+//a value that did not parse as the type asked for
+#[derive(Debug,Clone,PartialEq)]
+pub struct ValueError {
+	pub section: String,
+	pub key: String,
+	pub value: String,
 }
+//e.g. "invalid value "x" for depth in [sl]"
+impl Display for ValueError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error>{
+		write!(f,"invalid value {:?} for {} in [{}]",self.value,self.key,self.section)
+	}
+}
+impl std::error::Error for ValueError {}
 impl IntoIterator for ConfigFile {
 	type Item = ConfigSection;
 	type IntoIter = <Vec<ConfigSection> as IntoIterator>::IntoIter;
 	fn into_iter(self) -> Self::IntoIter {
-		self.sections.clone().into_iter()
+		self.sections.into_iter()
+	}
+}
+impl<'a> IntoIterator for &'a ConfigFile {
+	type Item = &'a ConfigSection;
+	type IntoIter = std::slice::Iter<'a,ConfigSection>;
+	fn into_iter(self) -> Self::IntoIter {
+		self.sections.iter()
 	}
 }
 impl From<&str> for ConfigFile {
 	fn from(data: &str) -> Self {
-		Self {
-			sections: parse_config(data),
+		let mut config = Self::new();
+		//reading from a &str can not fail
+		for event in Reader::new(data.as_bytes()).lenient().flatten() {
+			let origin = Origin {file: None, line: event.span().line};
+			match event {
+				Event::Section {name,..} => {
+					let mut section = ConfigSection::new(&name);
+					section.origin = Some(origin);
+					config.sections.push(section);
+				},
+				Event::Property {key,value,..} => {
+					let section = config.sections.last_mut().unwrap_or(&mut config.global);
+					section.insert(key,value,origin);
+				},
+				_ => (),
+			}
 		}
+		config
 	}
 }
-
-fn parse_config(raw_config: &str) -> Vec<ConfigSection>{
-	let lines = raw_config.split('\n').collect::<Vec<&str>>();
-	let mut sections = read_label("top_level",lines.into_iter());
-	//the first "section" will be everything before the first label, so strip it off
-	sections.remove(0);
-	sections
-}
 fn is_label(line: &str) -> bool {
-	if line.trim().chars().nth(0) == Some('[') {return true}
-	false
+	line.trim().starts_with('[')
 }
 fn extract_label(line: &str) -> String {
-	line.trim().trim_prefix("[").trim_suffix("]").into()
+	let line = line.trim();
+	let line = line.strip_prefix('[').unwrap_or(line);
+	line.strip_suffix(']').unwrap_or(line).into()
 }
-fn read_label<'a>(name: &str, mut lines: impl Iterator<Item = &'a str>) -> Vec<ConfigSection>{
-	let mut section = ConfigSection {
-		properties: HashMap::new(),
-		name: name.into(),
+//the name in a header, or the column the problem is at
+fn check_label(line: &str) -> Result<String,usize> {
+	let column = |byte: usize| line[..byte].chars().count() + 1;
+	let open = line.find('[').unwrap_or(0);
+	//====== find the "]" that is not in a quoted subsection name ======
+	let mut quoted = false;
+	let mut escaped = false;
+	let mut close = None;
+	for (index,c) in line.char_indices().skip(open + 1) {
+		match c {
+			_ if escaped => escaped = false,
+			'\\' if quoted => escaped = true,
+			'"' => quoted = !quoted,
+			']' if !quoted => {
+				close = Some(index);
+				break;
+			},
+			_ => (),
+		}
+	}
+	//where the "]" should have been
+	let Some(close) = close else {return Err(column(line.trim_end().len()))};
+	if !line[close + 1..].trim().is_empty() {return Err(column(close + 1))}
+	let name = line[open + 1..close].trim();
+	if section_path(name).is_none() {return Err(column(open))}
+	Ok(name.into())
+}
+//"parent.child" or `parent "child"` to ["parent","child"]
+//None if any part is empty or the quotes are wrong
+fn section_path(name: &str) -> Option<Vec<String>> {
+	let (parent,child) = match name.find('"') {
+		Some(quote) => (&name[..quote],Some(parse_value(&name[quote..]).ok()?)),
+		None => (name,None),
 	};
-	//====== for line in lines ======
-	loop {
-		//the flipping for loop calls into_iterator which moves. SO ANOYING
-		let line = match lines.next() { Some(v) => crop_comments(v), None => break, };
-		//====== if it is a valid property, add it to the hashmap ======
-		if is_property(line){
-			let (key, value) = extract_property(line);
-			let _ = section.properties.insert(key,value);
-		}
-		//====== recurse if another label is reached ======
-		if is_label(line){
-			let section_name = extract_label(line);
-			let mut result = vec![section];
-			result.append(&mut read_label(&section_name,lines));
-			return result
-		}
-	}
-	//base case
-	vec![section]
+	let mut path = parent.split('.').map(|part| part.trim().to_string()).collect::<Vec<_>>();
+	//`["child"]` is just a quoted name
+	if parent.trim().is_empty() && child.is_some() {path.clear()}
+	else if path.iter().any(String::is_empty) {return None}
+	path.extend(child);
+	if path.is_empty() {return None}
+	Some(path)
 }
 fn is_property(line: &str) -> bool {
 	line.contains('=')
 }
+//only splits on the first "=", so values can have them too
+//badly quoted values are left as they are
 fn extract_property(line: &str) -> (String,String){
-	let result = line.split('=').take(2).collect::<Vec<_>>();
-	(result[0].trim().into(), result[1].trim().into())
+	let (key,value) = line.split_once('=').unwrap_or((line,""));
+	(key.trim().into(), parse_value(value).unwrap_or_else(|_| value.trim().into()))
+}
+//====== lines ======
+//"#" and ";" start a comment at the start of a line or after whitespace, but not in a quoted value
+// url = http://a.com/#top    ; this is a comment
+fn crop_comments(line: &str) -> &str {
+	//quotes only count at the start of a value
+	let value_start = line.find('=').map(|equals| line.len() - line[equals + 1..].trim_start().len());
+	let mut quote = None;
+	let mut escaped = false;
+	let mut after_space = true;
+	for (index,c) in line.char_indices() {
+		match quote {
+			Some(_) if escaped => escaped = false,
+			Some('"') if c == '\\' => escaped = true,
+			Some(q) if c == q => quote = None,
+			Some(_) => (),
+			None if (c == '#' || c == ';') && after_space => return &line[..index],
+			None if (c == '"' || c == '\'') && Some(index) == value_start => quote = Some(c),
+			None => (),
+		}
+		after_space = c.is_whitespace();
+	}
+	line
 }
-fn crop_comments<'a>(line: &'a str) -> &'a str {
-	line.split('#').next().unwrap()
+//an odd number of backslashes at the end, so the next line carries on from this one
+fn continues(content: &str) -> bool {
+	let content = content.trim_end();
+	(content.len() - content.trim_end_matches('\\').len()) % 2 == 1
+}
+//each line with comments removed and continued lines joined up, along with the
+//line number it started on. The backslash is removed along with the indentation
+//of the next line, so "a \" followed by "    b" gives "a b"
+fn logical_lines(data: &str) -> Vec<(usize,String)> {
+	let mut lines: Vec<(usize,String)> = vec![];
+	let mut continued = false;
+	for (index,line) in data.lines().enumerate() {
+		let mut content = crop_comments(line).to_string();
+		if continued {content = content.trim_start().to_string()}
+		let next_continued = continues(&content);
+		if next_continued {
+			content.truncate(content.trim_end().len() - 1);
+		}
+		match lines.last_mut() {
+			Some((_,last)) if continued => last.push_str(&content),
+			_ => lines.push( (index + 1,content) ),
+		}
+		continued = next_continued;
+	}
+	lines
+}
+//====== values ======
+//removes the quotes and escapes from a value, or gives the byte offset and kind of the problem
+//double quotes understand \\ \" \' \n \t \r \0 \# and \;, single quotes take everything literally
+fn parse_value(raw: &str) -> Result<String,(usize,IniErrorKind)> {
+	let value = raw.trim();
+	let Some(quote) = value.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+		return Ok(value.into());
+	};
+	let mut result = String::new();
+	let mut chars = value.char_indices().skip(1);
+	while let Some((index,c)) = chars.next() {
+		if c == quote {
+			let rest = &value[index + 1..];
+			if !rest.trim().is_empty() {
+				return Err( (index + 1 + rest.len() - rest.trim_start().len(),IniErrorKind::TextAfterQuote) );
+			}
+			return Ok(result);
+		}
+		if c != '\\' || quote == '\'' {
+			result.push(c);
+			continue;
+		}
+		let Some((_,escaped)) = chars.next() else {break};
+		result.push(match escaped {
+			'n' => '\n',
+			't' => '\t',
+			'r' => '\r',
+			'0' => '\0',
+			'\\' | '"' | '\'' | '#' | ';' => escaped,
+			_ => return Err( (index,IniErrorKind::BadEscape(escaped)) ),
+		});
+	}
+	Err( (0,IniErrorKind::UnterminatedQuote) )
+}
+//the opposite of parse_value, only adds quotes if they are needed
+fn quote_value(value: &str) -> String {
+	let plain = value == value.trim()
+		&& !value.starts_with(['"','\''])
+		&& !value.contains(['\n','\r','\t','\0'])
+		&& crop_comments(value) == value
+		&& !continues(value);
+	if plain {return value.into()}
+	let mut quoted = String::from("\"");
+	for c in value.chars() {
+		match c {
+			'\n' => quoted += "\\n",
+			'\t' => quoted += "\\t",
+			'\r' => quoted += "\\r",
+			'\0' => quoted += "\\0",
+			'\\' | '"' => {quoted.push('\\'); quoted.push(c)},
+			_ => quoted.push(c),
+		}
+	}
+	quoted.push('"');
+	quoted
+}
+//======================= tests =======================
+#[cfg(test)]
+mod tests {
+	use super::*;
+	#[test]
+	fn parse_test(){
+		let config = ConfigFile::parse("#comment\n[sl]\ndepth = 3\n\n[paths]\nfilter = a=b # c\n").unwrap();
+		let sections = config.into_iter().collect::<Vec<_>>();
+		assert_eq!(sections.len(),2);
+		assert_eq!(sections[0].name(),"sl");
+		assert_eq!(sections[0].properties()["depth"],"3");
+		//the whole value, not just up to the second "="
+		assert_eq!(sections[1].properties()["filter"],"a=b");
+		assert_eq!(ConfigFile::from("[a]\nb = c=d\n"),ConfigFile::parse("[a]\nb = c=d\n").unwrap());
+	}
+	#[test]
+	fn parse_error_test(){
+		let error = |data| ConfigFile::parse(data).unwrap_err();
+		assert_eq!(error("[a]\nx = 1\n[b"),IniError {line: 3, column: 3, kind: IniErrorKind::BadHeader});
+		assert_eq!(error("[a]\n  [ ]"),IniError {line: 2, column: 3, kind: IniErrorKind::BadHeader});
+		assert_eq!(error("[a] b").kind,IniErrorKind::BadHeader);
+		assert_eq!(error("[a]\n  = 1"),IniError {line: 2, column: 3, kind: IniErrorKind::MissingKey});
+		assert_eq!(error("[a]\nnot a property"),IniError {line: 2, column: 1, kind: IniErrorKind::BadLine});
+		assert_eq!(error("[a]\n[b]\n[a]"),IniError {line: 3, column: 1, kind: IniErrorKind::DuplicateSection("a".into())});
+		let duplicate_key = error("[a]\nx = 1\n\tx = 2");
+		assert_eq!(duplicate_key,IniError {line: 3, column: 2, kind: IniErrorKind::DuplicateKey("x".into())});
+		assert_eq!(duplicate_key.to_string(),"line 3, column 2: key \"x\" is already defined in this section");
+		//the lenient version skips over all of it
+		assert_eq!(ConfigFile::from("[a]\nnot a property\nx = 1").into_iter().next().unwrap().properties().len(),1);
+		//and so does parse_with when asked to
+		let data = "[a]\nnot a property\nx = 1\nx = 2\n[a]\ny = 3";
+		let lenient = ParseOptions::new().lenient().duplicate_sections(DuplicateSections::KeepAll);
+		assert_eq!(ConfigFile::parse_with(data,&lenient),Ok(ConfigFile::from(data)));
+	}
+	#[test]
+	fn document_test(){
+		let data = "# tmux sessions\n[mc]\ncommand=java -jar server.jar # 4GB\ncwd=/srv/mc\n\n# the other one\n[web]\n  port = 80\n";
+		let mut document = Document::parse(data).unwrap();
+		//nothing changed, nothing different
+		assert_eq!(document.to_string(),data);
+		assert_eq!(document.sections().collect::<Vec<_>>(),vec!["mc","web"]);
+		assert_eq!(document.get("mc","command"),Some("java -jar server.jar"));
+		assert_eq!(document.properties("mc"),vec![("command","java -jar server.jar"),("cwd","/srv/mc")]);
+		//values are swapped in place, new keys copy the formatting of the one before
+		document.set("mc","command","java -Xmx8G -jar server.jar");
+		document.set("mc","restart","yes");
+		document.set("web","host","localhost");
+		assert_eq!(document.remove("mc","cwd"),Some("/srv/mc".into()));
+		assert_eq!(document.remove("mc","cwd"),None);
+		assert!(document.add_section("db"));
+		assert!(!document.add_section("db"));
+		document.set("db","path","a=b");
+		assert_eq!(document.to_string(),"# tmux sessions\n[mc]\ncommand=java -Xmx8G -jar server.jar # 4GB\nrestart=yes\n\n# the other one\n[web]\n  port = 80\n  host = localhost\n\n[db]\npath = a=b\n");
+		let config = ConfigFile::from(&document);
+		assert_eq!(config.into_iter().last().unwrap().properties()["path"],"a=b");
+		//line endings are kept
+		let mut document = Document::from("[a]\r\nx=1");
+		document.set("a","x","2");
+		let mut written = vec![];
+		document.write_to(&mut written).unwrap();
+		assert_eq!(written,b"[a]\r\nx=2");
+		assert!(Document::parse("[a]\n[a]").is_err());
+	}
+	#[test]
+	fn quoting_test(){
+		let data = concat!(
+			"; a comment\n",
+			"[mc]\n",
+			"command = echo \"#1\" # not part of it\n",
+			"url = http://a.com/#top;x ; comment\n",
+			"message = \"tab\\there \\\"quoted\\\" ; still here\"\n",
+			"literal = 'C:\\dir\\ # ;'\n",
+			"padded = \"  both ends  \"\n",
+			"args = -a \\\n",
+			"       -b \\ # comments can go here too\n",
+			"       -c\n",
+			"path = C:\\\\\n",
+			"next = 1\n",
+		);
+		let config = ConfigFile::parse(data).unwrap();
+		let section = config.into_iter().next().unwrap();
+		//echo "#1" only has the comment removed, the quotes are not at the start of the value
+		assert_eq!(section.get_str("command"),Some("echo \"#1\""));
+		assert_eq!(section.get_str("url"),Some("http://a.com/#top;x"));
+		assert_eq!(section.get_str("message"),Some("tab\there \"quoted\" ; still here"));
+		assert_eq!(section.get_str("literal"),Some("C:\\dir\\ # ;"));
+		assert_eq!(section.get_str("padded"),Some("  both ends  "));
+		assert_eq!(section.get_str("args"),Some("-a -b -c"));
+		//an escaped backslash at the end does not continue the line
+		assert_eq!(section.get_str("path"),Some("C:\\\\"));
+		assert_eq!(section.get_str("next"),Some("1"));
+		assert_eq!(ConfigFile::from(data),ConfigFile::parse(data).unwrap());
+		//====== errors ======
+		let error = |data| ConfigFile::parse(data).unwrap_err();
+		assert_eq!(error("[a]\nx = \"abc"),IniError {line: 2, column: 5, kind: IniErrorKind::UnterminatedQuote});
+		assert_eq!(error("[a]\nx = \"a\\qb\""),IniError {line: 2, column: 7, kind: IniErrorKind::BadEscape('q')});
+		assert_eq!(error("[a]\nx = 'a' b"),IniError {line: 2, column: 9, kind: IniErrorKind::TextAfterQuote});
+		//the line number is where the property started
+		assert_eq!(error("[a]\nx = \\\n  \"abc").line,2);
+		//====== documents keep the quotes and continued lines until they are changed ======
+		let mut document = Document::from(data);
+		assert_eq!(document.to_string(),data);
+		assert_eq!(document.get("mc","args"),Some("-a -b -c"));
+		assert_eq!(document.get("mc","message"),Some("tab\there \"quoted\" ; still here"));
+		document.set("mc","args","-d");
+		document.set("mc","padded"," # ");
+		document.set("mc","new","a\\");
+		let written = document.to_string();
+		assert!(written.contains("args = -d\npath"));
+		assert!(written.contains("padded = \" # \"\n"));
+		assert!(written.contains("new = \"a\\\\\"\n"));
+		let section = ConfigFile::parse(&written).unwrap().into_iter().next().unwrap();
+		assert_eq!(section.get_str("padded"),Some(" # "));
+		assert_eq!(section.get_str("new"),Some("a\\"));
+	}
+	#[test]
+	fn lookup_test(){
+		let data = "[mc server]\ncommand = java\nport = 1\n[web]\nport = 80\n[mc server]\nport = 2\n";
+		let error = ConfigFile::parse(data).unwrap_err();
+		assert_eq!(error,IniError {line: 6, column: 1, kind: IniErrorKind::DuplicateSection("mc server".into())});
+		let parse = |policy| ConfigFile::parse_with(data,&ParseOptions::new().duplicate_sections(policy)).unwrap();
+		//====== merge ======
+		let config = parse(DuplicateSections::Merge);
+		assert_eq!(config.iter().map(ConfigSection::name).collect::<Vec<_>>(),vec!["mc server","web"]);
+		assert_eq!(config.get("mc server","command"),Some("java"));
+		assert_eq!(config.get("mc server","port"),Some("2"));
+		assert_eq!(config.get("web","command"),None);
+		assert_eq!(config.get("db","port"),None);
+		//====== keep last ======
+		let config = parse(DuplicateSections::KeepLast);
+		assert_eq!((&config).into_iter().map(ConfigSection::name).collect::<Vec<_>>(),vec!["web","mc server"]);
+		assert_eq!(config.get("mc server","command"),None);
+		//====== keep all ======
+		let config = parse(DuplicateSections::KeepAll);
+		assert_eq!(config.sections_named("mc server").count(),2);
+		assert_eq!(config.get_section("mc server").unwrap().get_str("port"),Some("1"));
+		let ports = config.sections_named("mc server").filter_map(|section| section.get_str("port")).collect::<Vec<_>>();
+		assert_eq!(ports,vec!["1","2"]);
+		assert_eq!(config,ConfigFile::from(data));
+		//keys can still only be given once per header
+		let data = "[a]\nx = 1\n[a]\nx = 2\nx = 3";
+		let error = ConfigFile::parse_with(data,&ParseOptions::new().duplicate_sections(DuplicateSections::Merge)).unwrap_err();
+		assert_eq!(error.line,5);
+	}
+	#[test]
+	fn nested_sections_test(){
+		let data = "database_path = ~/.config/lever/packages.ini\n\n[remote \"origin\"]\nurl = a\n[remote.upstream]\nurl = b\n[build.release.flags]\nopt = 3\n";
+		let config = ConfigFile::parse(data).unwrap();
+		//====== global properties ======
+		assert_eq!(config.global().get_str("database_path"),Some("~/.config/lever/packages.ini"));
+		assert_eq!(config.global().name(),"");
+		assert_eq!(config.iter().count(),3);
+		assert_eq!(ConfigFile::from(data).global(),config.global());
+		//====== subsections ======
+		let origin = config.get_section("remote.origin").unwrap();
+		assert_eq!(origin.name(),"remote \"origin\"");
+		assert_eq!(origin.path(),["remote","origin"]);
+		assert_eq!(config.get_section("remote \"origin\""),Some(origin));
+		assert_eq!(config.get_path(&["remote","upstream"]).unwrap().get_str("url"),Some("b"));
+		assert_eq!(config.get("build.release.flags","opt"),Some("3"));
+		//====== as a tree ======
+		let tree = config.tree();
+		assert_eq!(tree.get("database_path"),Some("~/.config/lever/packages.ini"));
+		let remote = tree.child("remote").unwrap();
+		//only implied by its children
+		assert_eq!(remote.section(),None);
+		assert_eq!(remote.children().iter().map(SectionTree::name).collect::<Vec<_>>(),vec!["origin","upstream"]);
+		assert_eq!(remote.child("origin").unwrap().get("url"),Some("a"));
+		assert_eq!(tree.child("build").and_then(|build| build.child("release")?.child("flags")?.get("opt")),Some("3"));
+		//====== errors ======
+		let error = |data| ConfigFile::parse(data).unwrap_err();
+		assert_eq!(error("[a.b]\n[a \"b\"]").kind,IniErrorKind::DuplicateSection("a \"b\"".into()));
+		assert_eq!(error("[a..b]").kind,IniErrorKind::BadHeader);
+		assert_eq!(error("[a \"b]").kind,IniErrorKind::BadHeader);
+		//a "]" in quotes is part of the name
+		assert_eq!(ConfigFile::parse("[a \"]\"]").unwrap().get_path(&["a","]"]).unwrap().name(),"a \"]\"");
+		//====== documents ======
+		let mut document = Document::from(data);
+		assert_eq!(document.get("","database_path"),Some("~/.config/lever/packages.ini"));
+		assert_eq!(document.get("remote.origin","url"),Some("a"));
+		document.set("","jobs","4");
+		document.set("remote.origin","url","c");
+		assert!(document.to_string().starts_with("database_path = ~/.config/lever/packages.ini\njobs = 4\n\n[remote \"origin\"]\nurl = c\n"));
+	}
+	#[test]
+	fn include_test(){
+		let directory = std::env::temp_dir().join(format!("iniconfig_include_test_{}",std::process::id()));
+		std::fs::create_dir_all(directory.join("hosts")).unwrap();
+		let write = |name: &str, data: &str| std::fs::write(directory.join(name),data).unwrap();
+		write("base.ini","[mc]\ncommand = java\ncwd = /srv/mc\n[web]\ncommand = ./serve\n");
+		//relative to the file with the include, not the current directory
+		write("hosts/a.ini","include = ../base.ini\n[mc]\ncwd = /home/a/mc\n%include extra.ini\n");
+		write("hosts/extra.ini","[db]\ncommand = postgres\n");
+		let config = ConfigFile::load(directory.join("hosts/a.ini")).unwrap();
+		assert_eq!(config.iter().map(ConfigSection::name).collect::<Vec<_>>(),vec!["mc","web","db"]);
+		assert_eq!(config.get("mc","command"),Some("java"));
+		assert_eq!(config.get("mc","cwd"),Some("/home/a/mc"));
+		assert_eq!(config.get("db","command"),Some("postgres"));
+		let origin = config.origin("mc","cwd").unwrap();
+		assert_eq!(origin.file.as_deref(),Some(directory.join("hosts/a.ini").as_path()));
+		assert_eq!(origin.line,3);
+		assert!(config.origin("mc","command").unwrap().file.as_ref().unwrap().ends_with("base.ini"));
+		assert_eq!(config.get_section("db").unwrap().origin().unwrap().line,1);
+		//====== errors ======
+		write("hosts/b.ini","[a]\n%include c.ini\n");
+		write("hosts/c.ini","include = b.ini\n");
+		let error = ConfigFile::load(directory.join("hosts/b.ini")).unwrap_err();
+		let LoadErrorKind::IncludeCycle(files) = &error.kind else {panic!("{error}")};
+		assert_eq!(files.len(),3);
+		assert!(error.path.ends_with("c.ini"));
+		write("hosts/d.ini","[a]\n%include missing.ini\n");
+		let error = ConfigFile::load(directory.join("hosts/d.ini")).unwrap_err();
+		assert!(matches!(error.kind,LoadErrorKind::Io(_)));
+		assert!(error.path.ends_with("missing.ini"));
+		write("hosts/e.ini","include = f.ini\n");
+		write("hosts/f.ini","[a]\n[a]\n");
+		let error = ConfigFile::load(directory.join("hosts/e.ini")).unwrap_err();
+		assert!(error.to_string().ends_with("f.ini: line 2, column 1: section [a] is already defined"));
+		//====== include as a key ======
+		write("cc.ini","[cc]\ninclude = /usr/include\n");
+		let config = ConfigFile::load(directory.join("cc.ini")).unwrap();
+		assert_eq!(config.get("cc","include"),Some("/usr/include"));
+		//====== pasted in ======
+		write("outer.ini","[a]\n%include inner.ini\nx = 1\n");
+		write("inner.ini","y = 2\n[b]\nz = 3\n");
+		let config = ConfigFile::load(directory.join("outer.ini")).unwrap();
+		assert_eq!(config.get("a","y"),Some("2"));
+		assert_eq!(config.get("b","z"),Some("3"));
+		assert_eq!(config.get("b","x"),Some("1"));
+		assert_eq!(config.get("a","x"),None);
+		//a key set on both sides of the include is set twice in the same section
+		write("twice.ini","[a]\ny = 1\n%include inner.ini\n");
+		let error = ConfigFile::load(directory.join("twice.ini")).unwrap_err();
+		assert!(error.to_string().ends_with("inner.ini: line 1, column 1: key \"y\" is already defined in this section"),"{error}");
+		//====== lenient ======
+		let lenient = ParseOptions::new().lenient().duplicate_sections(DuplicateSections::KeepAll);
+		write("lenient.ini","not a property\n%include base.ini\n[web]\nport = 80\n");
+		let config = ConfigFile::load_with(directory.join("lenient.ini"),&lenient).unwrap();
+		assert_eq!(config.iter().map(ConfigSection::name).collect::<Vec<_>>(),vec!["mc","web"]);
+		assert_eq!(config.get("mc","command"),Some("java"));
+		assert_eq!(config.get("web","port"),Some("80"));
+		//reading without load skips the include
+		let data = "%include base.ini\n[web]\nport = 80\n";
+		assert_eq!(ConfigFile::parse_with(data,&lenient),Ok(ConfigFile::from(data)));
+		//====== keep last ======
+		//the included file throws away a section before the one the includer is in
+		let options = ParseOptions::new().duplicate_sections(DuplicateSections::KeepLast);
+		write("keep.ini","[a]\nx = 1\n[b]\n%include keep_inner.ini\ny = 2\n[b]\nz = 3\n");
+		write("keep_inner.ini","[a]\n[a]\nw = 4\n");
+		let config = ConfigFile::load_with(directory.join("keep.ini"),&options).unwrap();
+		assert_eq!(config.iter().map(ConfigSection::name).collect::<Vec<_>>(),vec!["a","b"]);
+		assert_eq!(config.get("a","x"),None);
+		assert_eq!(config.get("a","w"),Some("4"));
+		assert_eq!(config.get("a","y"),Some("2"));
+		assert_eq!(config.get("b","z"),Some("3"));
+		std::fs::remove_dir_all(&directory).unwrap();
+		//====== layers ======
+		let system = ConfigFile::parse("jobs = 1\n[mc]\ncommand = java\nignore = a, b\n").unwrap();
+		let user = ConfigFile::parse("[mc]\nignore = c\n[web]\nport = 80").unwrap();
+		let project = ConfigFile::parse("jobs = 4\n[web]\nport = 8080").unwrap();
+		let config = ConfigFile::layered([system,user,project]);
+		assert_eq!(config.get("","jobs"),Some("4"));
+		assert_eq!(config.get("mc","command"),Some("java"));
+		//later layers replace lists rather than adding to them
+		assert_eq!(config.get_section("mc").unwrap().get_list("ignore"),vec!["c"]);
+		assert_eq!(config.get("web","port"),Some("8080"));
+		assert_eq!(config.origin("web","port"),Some(&Origin {file: None, line: 3}));
+	}
+	#[test]
+	fn interpolate_test(){
+		unsafe {std::env::set_var("INICONFIG_TEST_HOME","/home/a")}
+		let data = concat!(
+			"root = ${env:INICONFIG_TEST_HOME}/servers\n",
+			"[paths]\n",
+			"logs = ${root}/logs\n",
+			"[mc]\n",
+			"name = mc\n",
+			"cwd = ${root}/${name}\n",
+			"log = ${paths:logs}/${name}.log\n",
+			"price = $5 $${not a reference}\n",
+		);
+		let mut config = ConfigFile::parse(data).unwrap();
+		assert_eq!(config.get_interpolated("mc","log"),Ok(Some("/home/a/servers/logs/mc.log".into())));
+		assert_eq!(config.get_interpolated("mc","missing"),Ok(None));
+		//still as written until interpolate is called
+		assert_eq!(config.get("mc","cwd"),Some("${root}/${name}"));
+		config.interpolate().unwrap();
+		assert_eq!(config.get("mc","cwd"),Some("/home/a/servers/mc"));
+		assert_eq!(config.get("paths","logs"),Some("/home/a/servers/logs"));
+		assert_eq!(config.get("mc","price"),Some("$5 ${not a reference}"));
+		//====== errors ======
+		let error = |data| ConfigFile::parse(data).unwrap().interpolate().unwrap_err();
+		let cycle = error("[a]\nx = ${y}\ny = ${b:z}\n[b]\nz = ${a:x}\n");
+		assert_eq!(cycle.kind,InterpolateErrorKind::Cycle(vec!["a:x".into(),"a:y".into(),"b:z".into(),"a:x".into()]));
+		assert_eq!(cycle.origin,Some(Origin {file: None, line: 5}));
+		let undefined = error("[a]\nx = ${nope}\n");
+		assert_eq!(undefined.kind,InterpolateErrorKind::Undefined("nope".into()));
+		assert_eq!(undefined.to_string(),"line 2: x in [a]: ${nope} is not defined");
+		assert_eq!(error("[a]\nx = ${env:INICONFIG_TEST_UNSET}").kind,InterpolateErrorKind::Undefined("env:INICONFIG_TEST_UNSET".into()));
+		assert_eq!(error("[a]\nx = ${b:x}\n[b]").kind,InterpolateErrorKind::Undefined("b:x".into()));
+		assert_eq!(error("[a]\nx = ${y").kind,InterpolateErrorKind::Unterminated);
+		//====== repeated sections ======
+		//a section's own name means itself, and sections with the same name are not the same property
+		let options = ParseOptions::new().duplicate_sections(DuplicateSections::KeepAll);
+		let mut config = ConfigFile::parse_with("[web]\na = ${web:b}\nb = 1\n[web]\nb = ${web:a}\nc = ${web:b}\n",&options).unwrap();
+		config.interpolate().unwrap();
+		let sections = config.into_iter().collect::<Vec<_>>();
+		assert_eq!(sections[0].get_str("a"),Some("1"));
+		assert_eq!(sections[1].get_str("b"),Some("1"));
+		assert_eq!(sections[1].get_str("c"),Some("1"));
+	}
+	#[test]
+	fn schema_test(){
+		let schema = Schema::new()
+			.global(SectionSchema::new("").key(KeySchema::new("name")))
+			.section(SectionSchema::new("log").required()
+				.key(KeySchema::new("level").one_of(&["debug","info","warn"]).default("info"))
+				.key(KeySchema::new("rotate").value(ValueType::Bool)))
+			.section(SectionSchema::matching("server.*")
+				.key(KeySchema::new("command").required())
+				.key(KeySchema::new("port").value(ValueType::Integer))
+				.key(KeySchema::new("ratios").value(ValueType::Float).list())
+				.key(KeySchema::new("cwd")));
+		let mut config = ConfigFile::parse("name = test\n[log]\nrotate = on\n[server.mc]\ncommand = ./run\nratios = 1, 0.5\n").unwrap();
+		assert_eq!(config.validate(&schema),Ok(()));
+		assert_eq!(config.get("log","level"),Some("info"));
+		assert_eq!(config.get("log","rotate"),Some("on"));
+		//====== every problem at once ======
+		let data = concat!(
+			"name = test\n",
+			"[log]\n",
+			"level = loud\n",
+			"[server.mc]\n",
+			"comand = ./run\n",
+			"port = eighty\n",
+			"ratios = 1, half\n",
+			"[servers]\n",
+		);
+		let errors = ConfigFile::parse(data).unwrap().validate(&schema).unwrap_err();
+		let kinds = errors.iter().map(|error| (error.origin.as_ref().map(|origin| origin.line),error.kind.clone())).collect::<Vec<_>>();
+		assert_eq!(kinds,vec![
+			(Some(3),SchemaErrorKind::NotAllowed("loud".into(),vec!["debug".into(),"info".into(),"warn".into()])),
+			(Some(4),SchemaErrorKind::MissingKey),
+			(Some(5),SchemaErrorKind::UnknownKey(Some("command".into()))),
+			(Some(6),SchemaErrorKind::InvalidValue("eighty".into(),ValueType::Integer)),
+			(Some(7),SchemaErrorKind::InvalidValue("half".into(),ValueType::Float)),
+			(Some(8),SchemaErrorKind::UnknownSection),
+		]);
+		assert_eq!(errors[2].to_string(),"line 5: unknown key comand in [server.mc], did you mean command?");
+		assert_eq!(errors[1].to_string(),"line 4: missing key command in [server.mc]");
+		let errors = ConfigFile::parse("other = 1\n").unwrap().validate(&schema).unwrap_err();
+		assert_eq!(errors[0].kind,SchemaErrorKind::UnknownKey(None));
+		assert_eq!(errors[1].to_string(),"missing section [log]");
+		//errors from included files are kept together rather than mixed in by line
+		let directory = std::env::temp_dir().join(format!("iniconfig_schema_test_{}",std::process::id()));
+		std::fs::create_dir_all(&directory).unwrap();
+		std::fs::write(directory.join("root.ini"),"[a]\nbad = 1\n%include inc.ini\n[c]\nbad = 3\n").unwrap();
+		std::fs::write(directory.join("inc.ini"),"[b]\nx = 1\nbad = 2\n").unwrap();
+		let mut config = ConfigFile::load(directory.join("root.ini")).unwrap();
+		std::fs::remove_dir_all(&directory).unwrap();
+		let schema = Schema::new().section(SectionSchema::matching("*").key(KeySchema::new("x")));
+		let errors = config.validate(&schema).unwrap_err();
+		let places = errors.iter()
+			.map(|error| error.origin.as_ref().map(|origin| (origin.file.as_ref().unwrap().file_name().unwrap().to_str().unwrap(),origin.line)).unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(places,vec![("inc.ini",3),("root.ini",2),("root.ini",5)]);
+		//transposed letters are one edit
+		assert_eq!(edit_distance("comand","command"),1);
+		assert_eq!(edit_distance("dpeth","depth"),1);
+		assert_eq!(edit_distance("",""),0);
+	}
+	#[test]
+	fn reader_test(){
+		let data = "# top\r\n[a]\r\nkey = \"x\" ; note\r\nlong = 1 \\\r\n  2\r\n%include other.ini\r\n";
+		let events = Reader::new(data.as_bytes()).collect::<Result<Vec<_>,_>>().unwrap();
+		assert_eq!(events,vec![
+			Event::Comment {text: "top".into(), span: Span {start: 0, end: 5, line: 1, column: 1}},
+			Event::Section {name: "a".into(), span: Span {start: 7, end: 10, line: 2, column: 1}},
+			Event::Property {key: "key".into(), value: "x".into(), span: Span {start: 12, end: 28, line: 3, column: 1}},
+			Event::Comment {text: "note".into(), span: Span {start: 22, end: 28, line: 3, column: 11}},
+			Event::Property {key: "long".into(), value: "1 2".into(), span: Span {start: 30, end: 45, line: 4, column: 1}},
+			Event::Include {path: "other.ini".into(), span: Span {start: 47, end: 65, line: 6, column: 1}},
+		]);
+		assert_eq!(&data[30..45],"long = 1 \\\r\n  2");
+		//====== bad lines can be skipped past, only read errors stop the reader ======
+		let mut reader = Reader::new("[a]\nb\n[c]\n".as_bytes());
+		assert!(matches!(reader.next(),Some(Ok(Event::Section {..}))));
+		assert_eq!(reader.next(),Some(Err(IniError {line: 2, column: 1, kind: IniErrorKind::BadLine})));
+		assert!(matches!(reader.next(),Some(Ok(Event::Section {..}))));
+		assert_eq!(reader.next(),None);
+		let bad_utf8: &[u8] = b"[a]\nb = \xff\n";
+		assert_eq!(ConfigFile::read(bad_utf8).unwrap_err().kind,IniErrorKind::Io(std::io::ErrorKind::InvalidData));
+		assert_eq!(ConfigFile::parse("%include a.ini\n").unwrap_err().kind,IniErrorKind::BadLine);
+		//====== lenient ======
+		let events = Reader::new("junk\n[a=b]\n".as_bytes()).lenient().collect::<Result<Vec<_>,_>>().unwrap();
+		assert_eq!(events.len(),2);
+		//====== lots of sections, which used to mean lots of recursion ======
+		let data = (0..200_000).map(|i| format!("[s{i}]\nk = {i}\n")).collect::<String>();
+		let config = ConfigFile::from(data.as_str());
+		assert_eq!(config.get("s199999","k"),Some("199999"));
+		assert_eq!(config.get_section("s5").unwrap().origin(),Some(&Origin {file: None, line: 11}));
+	}
+	#[test]
+	fn typed_values_test(){
+		let config = ConfigFile::from("[sl]\ndepth = 3\ncolour = Off\nwidth = wide\nignore = .git, target\nignore = node_modules\n");
+		let section = config.into_iter().next().unwrap();
+		assert_eq!(section.get::<usize>("depth"),Ok(Some(3)));
+		assert_eq!(section.get::<usize>("missing"),Ok(None));
+		let error = section.get::<usize>("width").unwrap_err();
+		assert_eq!(error.to_string(),"invalid value \"wide\" for width in [sl]");
+		assert_eq!(section.get_bool("colour"),Ok(Some(false)));
+		assert!(section.get_bool("width").is_err());
+		assert_eq!(parse_bool("YES"),Some(true));
+		assert_eq!(section.get_all("ignore"),vec![".git, target","node_modules"]);
+		assert_eq!(section.get_list("ignore"),vec![".git","target","node_modules"]);
+		assert_eq!(section.get_str("ignore"),Some("node_modules"));
+	}
+	#[cfg(feature = "serde")]
+	#[test]
+	fn deserialize_test(){
+		use serde::Deserialize;
+		#[derive(Debug,Deserialize,PartialEq)]
+		#[serde(rename_all = "lowercase")]
+		enum Restart {Always,Never}
+		#[derive(Debug,Deserialize,PartialEq)]
+		struct Session {
+			command: String,
+			cwd: Option<String>,
+			restart: Restart,
+			#[serde(default)]
+			detached: bool,
+			windows: u8,
+			#[serde(default)]
+			env: Vec<String>,
+		}
+		let config = ConfigFile::parse("[mc]\ncommand = java -jar server.jar\nrestart = always\ndetached = yes\nwindows = 2\nenv = A=1, B=2\n[web]\ncommand = ./serve\ncwd = /srv\nrestart = never\nwindows = 1\n").unwrap();
+		let sessions = config.deserialize::<std::collections::BTreeMap<String,Session>>().unwrap();
+		assert_eq!(sessions["mc"],Session {
+			command: "java -jar server.jar".into(), cwd: None, restart: Restart::Always,
+			detached: true, windows: 2, env: vec!["A=1".into(),"B=2".into()],
+		});
+		assert_eq!(sessions["web"].cwd.as_deref(),Some("/srv"));
+		let section = config.into_iter().next().unwrap();
+		assert_eq!(section.deserialize::<Session>().unwrap().windows,2);
+		//global keys and subsections nest
+		#[derive(Debug,Deserialize,PartialEq)]
+		struct Remote {url: String}
+		#[derive(Debug,Deserialize,PartialEq)]
+		struct Git {
+			jobs: u32,
+			remote: std::collections::BTreeMap<String,Remote>,
+		}
+		let git = ConfigFile::parse("jobs = 4\n[remote \"origin\"]\nurl = a\n[remote.upstream]\nurl = b\n").unwrap()
+			.deserialize::<Git>().unwrap();
+		assert_eq!(git.jobs,4);
+		assert_eq!(git.remote["upstream"],Remote {url: "b".into()});
+		let error = ConfigFile::from("[a]\ncommand = x\nrestart = never\nwindows = many").into_iter().next().unwrap()
+			.deserialize::<Session>().unwrap_err();
+		assert_eq!(error.to_string(),"invalid value \"many\" for windows in [a]");
+	}
 }
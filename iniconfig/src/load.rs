@@ -0,0 +1,118 @@
+//======================= includes and layers =======================
+use crate::{ConfigFile,Event,FileParser,IniError,Origin,ParseOptions};
+use std::fmt::{Display,Formatter};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path,PathBuf};
+
+#[derive(Debug)]
+pub struct LoadError {
+	//the file the error is in, which might be one that was included
+	pub path: PathBuf,
+	pub kind: LoadErrorKind,
+}
+#[derive(Debug)]
+pub enum LoadErrorKind {
+	Io(std::io::Error),
+	Parse(IniError),
+	//the files that include each other, the first and last are the same
+	IncludeCycle(Vec<PathBuf>),
+}
+//e.g. "hosts/a.ini: line 3, column 1: bad section header"
+impl Display for LoadError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error>{
+		write!(f,"{}: ",self.path.display())?;
+		match &self.kind {
+			LoadErrorKind::Io(error) => write!(f,"{error}"),
+			LoadErrorKind::Parse(error) => write!(f,"{error}"),
+			LoadErrorKind::IncludeCycle(files) => {
+				let files = files.iter().map(|file| file.display().to_string()).collect::<Vec<_>>();
+				write!(f,"include cycle {}",files.join(" -> "))
+			},
+		}
+	}
+}
+impl std::error::Error for LoadError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match &self.kind {
+			LoadErrorKind::Io(error) => Some(error),
+			LoadErrorKind::Parse(error) => Some(error),
+			LoadErrorKind::IncludeCycle(_) => None,
+		}
+	}
+}
+impl ConfigFile {
+	//reads a file along with any it includes with `%include path`, relative to the file the
+	//directive is in. `include = path` works too, but only before the first section header of a file,
+	//anywhere else include is an ordinary key. The included file is read as if it were pasted in,
+	//so it starts in the includer's section and the includer carries on in the included file's last one,
+	//except sections it shares with other files are merged and later values win
+	pub fn load(path: impl AsRef<Path>) -> Result<Self,LoadError> {
+		Self::load_with(path,&ParseOptions::default())
+	}
+	pub fn load_with(path: impl AsRef<Path>, options: &ParseOptions) -> Result<Self,LoadError> {
+		let mut config = Self::new();
+		let mut parser = FileParser::new(options,None);
+		include(&mut config,path.as_ref(),options,&mut vec![],&mut parser)?;
+		Ok(config)
+	}
+	//====== layers ======
+	//adds another file on top of this one, e.g. system then user then project
+	//sections are merged and the layer's values win
+	pub fn layer(&mut self, layer: ConfigFile) {
+		self.global.merge(layer.global);
+		for section in layer.sections {
+			match self.sections.iter_mut().find(|existing| existing.path == section.path) {
+				Some(existing) => existing.merge(section),
+				None => self.sections.push(section),
+			}
+		}
+	}
+	//the layers in order, the last one wins
+	pub fn layered(layers: impl IntoIterator<Item = ConfigFile>) -> Self {
+		let mut config = Self::new();
+		for layer in layers {config.layer(layer)}
+		config
+	}
+	//where the value of a key came from, e.g. to tell the user which file to edit
+	pub fn origin(&self, section: &str, key: &str) -> Option<&Origin> {
+		self.get_section(section)?.key_origin(key)
+	}
+}
+//stack is the files currently being read, to catch cycles
+fn include(config: &mut ConfigFile, path: &Path, options: &ParseOptions, stack: &mut Vec<PathBuf>, includer: &mut FileParser) -> Result<(),LoadError> {
+	let error = |kind| LoadError {path: path.into(), kind};
+	let canonical = path.canonicalize().map_err(|e| error(LoadErrorKind::Io(e)))?;
+	let file = File::open(path).map_err(|e| error(LoadErrorKind::Io(e)))?;
+	stack.push(canonical);
+	let mut parser = FileParser::new(options,Some(path.into()));
+	//pasted in, so carry on in the includer's section and hand the last one back at the end
+	parser.continue_from(includer);
+	for event in options.reader(BufReader::new(file)) {
+		let event = event.map_err(|e| error(LoadErrorKind::Parse(e)))?;
+		match include_path(&event,parser.headers.is_empty()) {
+			Some(included) => {
+				let included = path.parent().unwrap_or(Path::new("")).join(included);
+				//the error is in the file with the include that closes the loop
+				if let Ok(canonical) = included.canonicalize() && stack.contains(&canonical) {
+					let mut files = stack.clone();
+					files.push(canonical);
+					return Err(error(LoadErrorKind::IncludeCycle(files)));
+				}
+				include(config,&included,options,stack,&mut parser)?;
+			},
+			None => parser.event(config,event).map_err(|e| error(LoadErrorKind::Parse(e)))?,
+		}
+	}
+	stack.pop();
+	includer.continue_from(&mut parser);
+	Ok(())
+}
+//the path in `%include path`, or `include = path` before the first header
+fn include_path(event: &Event, before_headers: bool) -> Option<&str> {
+	match event {
+		Event::Include {path,..} => Some(path),
+		Event::Property {key,value,..} if before_headers && key == "include" => Some(value),
+		_ => None,
+	}
+}
@@ -0,0 +1,179 @@
+//======================= pull parser =======================
+// for event in Reader::new(BufReader::new(File::open(path)?)) {
+// 	match event? {
+// 		Event::Section {name,..} => println!("[{name}]"),
+// 		Event::Property {key,value,..} => println!("{key} = {value}"),
+// 		_ => (),
+// 	}
+// }
+//reads a line at a time, so the whole file never has to be in memory
+//a bad line gives an error and reading carries on, only failing to read stops it
+use crate::{IniError,IniErrorKind,check_label,continues,crop_comments,extract_label,extract_property,is_label,is_property,parse_value};
+use std::collections::VecDeque;
+use std::io::BufRead;
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum Event {
+	Section {name: String, span: Span},
+	//the value has its quotes and escapes removed
+	Property {key: String, value: String, span: Span},
+	//the text after the "#" or ";"
+	Comment {text: String, span: Span},
+	//`%include path`, ConfigFile::load follows these
+	Include {path: String, span: Span},
+}
+//where an event is in the input
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct Span {
+	//byte offsets, a property continued over several lines covers all of them
+	pub start: usize,
+	pub end: usize,
+	//where it starts, both count from 1
+	pub line: usize,
+	pub column: usize,
+}
+impl Event {
+	pub fn span(&self) -> &Span {
+		match self {
+			Event::Section {span,..} | Event::Property {span,..} | Event::Comment {span,..} | Event::Include {span,..} => span,
+		}
+	}
+}
+pub struct Reader<R> {
+	input: R,
+	lenient: bool,
+	//the last line read and the offset of the next one
+	line: usize,
+	offset: usize,
+	//a line can give a property and a comment, so they wait here
+	pending: VecDeque<Result<Event,IniError>>,
+	done: bool,
+}
+impl<R: BufRead> Reader<R> {
+	pub fn new(input: R) -> Self {
+		Self {input, lenient: false, line: 0, offset: 0, pending: VecDeque::new(), done: false}
+	}
+	//like ConfigFile::from, lines that do not make sense are skipped instead of being an error
+	pub fn lenient(mut self) -> Self {
+		self.lenient = true;
+		self
+	}
+	//the next line without its line ending, and the offset it starts at
+	fn read_line(&mut self) -> Result<Option<(String,usize)>,IniError> {
+		let mut line = String::new();
+		let read = self.input.read_line(&mut line)
+			.map_err(|e| IniError {line: self.line + 1, column: 1, kind: IniErrorKind::Io(e.kind())})?;
+		if read == 0 {return Ok(None)}
+		let start = self.offset;
+		self.offset += read;
+		self.line += 1;
+		if line.ends_with('\n') {line.pop();}
+		if line.ends_with('\r') {line.pop();}
+		Ok(Some( (line,start) ))
+	}
+	//reads one logical line, joining continued lines, and queues up its events
+	fn read_logical_line(&mut self) -> Result<(),IniError> {
+		let mut content = String::new();
+		let mut comments = vec![];
+		let mut first = None;
+		let mut end = self.offset;
+		loop {
+			let Some((line,start)) = self.read_line()? else {
+				self.done = true;
+				break;
+			};
+			let continued = first.is_some();
+			first.get_or_insert((self.line,start));
+			end = start + line.len();
+			//====== comments ======
+			let cropped = crop_comments(&line);
+			if cropped.len() < line.len() {
+				let marker = cropped.len();
+				comments.push(Ok(Event::Comment {
+					text: line[marker + 1..].trim().into(),
+					span: Span {start: start + marker, end, line: self.line, column: cropped.chars().count() + 1},
+				}));
+			}
+			//====== continuation ======
+			let mut part = match continued {
+				true => cropped.trim_start(),
+				false => cropped,
+			}.to_string();
+			let next_continued = continues(&part);
+			if next_continued {
+				part.truncate(part.trim_end().len() - 1);
+			}
+			content.push_str(&part);
+			if !next_continued {break}
+		}
+		let Some((line,start)) = first else {return Ok(())};
+		if !content.trim().is_empty() {
+			let indent = content.len() - content.trim_start().len();
+			let span = Span {start: start + indent, end, line, column: content[..indent].chars().count() + 1};
+			match self.lenient {
+				true => self.pending.extend(lenient_events(&content,span).into_iter().map(Ok)),
+				false => self.pending.push_back(event(&content,span)),
+			}
+		}
+		self.pending.extend(comments);
+		Ok(())
+	}
+}
+impl<R: BufRead> Iterator for Reader<R> {
+	type Item = Result<Event,IniError>;
+	fn next(&mut self) -> Option<Self::Item> {
+		while self.pending.is_empty() && !self.done {
+			if let Err(e) = self.read_logical_line() {
+				self.done = true;
+				return Some(Err(e));
+			}
+		}
+		self.pending.pop_front()
+	}
+}
+//a section header, property or include, anything else is an error
+fn event(line: &str, span: Span) -> Result<Event,IniError> {
+	//columns start at 1 and count characters, not bytes
+	let column = |byte: usize| line[..byte].chars().count() + 1;
+	let error = |column,kind| IniError {line: span.line, column, kind};
+	let trimmed = line.trim();
+	if trimmed.starts_with("%include") {
+		let raw_path = &line[line.find("%include").unwrap_or(0) + "%include".len()..];
+		let path_start = line.len() - raw_path.trim_start().len();
+		let path = parse_value(raw_path).map_err(|(offset,kind)| error(column(path_start + offset),kind))?;
+		return Ok(Event::Include {path, span});
+	}
+	if is_label(line) {
+		let name = check_label(line).map_err(|column| error(column,IniErrorKind::BadHeader))?;
+		return Ok(Event::Section {name, span});
+	}
+	if is_property(line) {
+		let (key,raw_value) = line.split_once('=').unwrap_or((line,""));
+		let key = key.trim().to_string();
+		if key.is_empty() {return Err(error(span.column,IniErrorKind::MissingKey))}
+		let value_start = line.len() - raw_value.trim_start().len();
+		let value = parse_value(raw_value)
+			.map_err(|(offset,kind)| error(column(value_start + offset),kind))?;
+		return Ok(Event::Property {key, value, span});
+	}
+	Err(error(span.column,IniErrorKind::BadLine))
+}
+//what ConfigFile::from has always done, a line with "=" is a property even if it is
+//also a header, and anything else is skipped. Includes are passed on for ConfigFile::load
+fn lenient_events(line: &str, span: Span) -> Vec<Event> {
+	let mut events = vec![];
+	if let Some(raw_path) = line.trim().strip_prefix("%include") {
+		//a path that does not parse is used as written
+		let path = parse_value(raw_path).unwrap_or_else(|_| raw_path.trim().into());
+		events.push(Event::Include {path, span});
+		return events;
+	}
+	if is_property(line) {
+		let (key,value) = extract_property(line);
+		events.push(Event::Property {key, value, span});
+	}
+	if is_label(line) {
+		events.push(Event::Section {name: extract_label(line), span});
+	}
+	events
+}
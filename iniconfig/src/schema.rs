@@ -0,0 +1,302 @@
+//======================= schema validation =======================
+// let schema = Schema::new()
+// 	.section(SectionSchema::matching("*")
+// 		.key(KeySchema::new("command").required())
+// 		.key(KeySchema::new("restart").value(ValueType::Bool).default("no")));
+// config.validate(&schema)?;
+use crate::{ConfigFile,ConfigSection,Origin,parse_bool};
+use std::fmt::{Display,Formatter};
+
+#[derive(Debug,Clone,PartialEq,Default)]
+pub struct Schema {
+	//None means the global section can not have any properties
+	global: Option<SectionSchema>,
+	sections: Vec<SectionSchema>,
+}
+#[derive(Debug,Clone,PartialEq)]
+pub struct SectionSchema {
+	//an exact name, or a pattern where * matches anything
+	name: String,
+	pattern: bool,
+	required: bool,
+	keys: Vec<KeySchema>,
+	allow_unknown_keys: bool,
+}
+#[derive(Debug,Clone,PartialEq)]
+pub struct KeySchema {
+	name: String,
+	value: ValueType,
+	required: bool,
+	default: Option<String>,
+	//empty means anything of the right type
+	allowed: Vec<String>,
+	//checks each item of a comma separated list
+	list: bool,
+}
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum ValueType {
+	String,
+	Integer,
+	Float,
+	//anything parse_bool takes
+	Bool,
+}
+impl Schema {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	//the properties allowed before the first section header
+	pub fn global(mut self, global: SectionSchema) -> Self {
+		self.global = Some(global);
+		self
+	}
+	//sections are checked against the first schema that matches their name
+	pub fn section(mut self, section: SectionSchema) -> Self {
+		self.sections.push(section);
+		self
+	}
+	fn find(&self, section: &ConfigSection) -> Option<&SectionSchema> {
+		if section.name.is_empty() {return self.global.as_ref()}
+		self.sections.iter().find(|schema| schema.matches(section))
+	}
+}
+impl SectionSchema {
+	pub fn new(name: &str) -> Self {
+		Self {name: name.into(), pattern: false, required: false, keys: vec![], allow_unknown_keys: false}
+	}
+	//e.g. "server.*", "*" matches any section
+	pub fn matching(pattern: &str) -> Self {
+		Self {pattern: true, ..Self::new(pattern)}
+	}
+	//only for exact names, a pattern is never required
+	pub fn required(mut self) -> Self {
+		self.required = true;
+		self
+	}
+	pub fn key(mut self, key: KeySchema) -> Self {
+		self.keys.push(key);
+		self
+	}
+	//otherwise keys not in the schema are an error
+	pub fn allow_unknown_keys(mut self) -> Self {
+		self.allow_unknown_keys = true;
+		self
+	}
+	fn matches(&self, section: &ConfigSection) -> bool {
+		match self.pattern {
+			true => glob_match(&self.name,&section.name) || glob_match(&self.name,&section.path.join(".")),
+			false => section.is_named(&self.name),
+		}
+	}
+}
+impl KeySchema {
+	pub fn new(name: &str) -> Self {
+		Self {name: name.into(), value: ValueType::String, required: false, default: None, allowed: vec![], list: false}
+	}
+	pub fn value(mut self, value: ValueType) -> Self {
+		self.value = value;
+		self
+	}
+	pub fn required(mut self) -> Self {
+		self.required = true;
+		self
+	}
+	//filled in by validate if the key is missing
+	pub fn default(mut self, default: &str) -> Self {
+		self.default = Some(default.into());
+		self
+	}
+	//e.g. `.one_of(&["debug","info","warn"])`
+	pub fn one_of(mut self, allowed: &[&str]) -> Self {
+		self.allowed = allowed.iter().map(|value| value.to_string()).collect();
+		self
+	}
+	pub fn list(mut self) -> Self {
+		self.list = true;
+		self
+	}
+	fn check(&self, value: &str) -> Option<SchemaErrorKind> {
+		let valid = match self.value {
+			ValueType::String => true,
+			ValueType::Integer => value.parse::<i64>().is_ok(),
+			ValueType::Float => value.parse::<f64>().is_ok(),
+			ValueType::Bool => parse_bool(value).is_some(),
+		};
+		if !valid {
+			return Some(SchemaErrorKind::InvalidValue(value.into(),self.value));
+		}
+		if !self.allowed.is_empty() && !self.allowed.iter().any(|allowed| allowed == value) {
+			return Some(SchemaErrorKind::NotAllowed(value.into(),self.allowed.clone()));
+		}
+		None
+	}
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct SchemaError {
+	//"" for the global section
+	pub section: String,
+	//None if the error is about the whole section
+	pub key: Option<String>,
+	//the property, or the header if it is about a section or a missing key
+	pub origin: Option<Origin>,
+	pub kind: SchemaErrorKind,
+}
+#[derive(Debug,Clone,PartialEq)]
+pub enum SchemaErrorKind {
+	UnknownSection,
+	MissingSection,
+	//closest key in the schema
+	UnknownKey(Option<String>),
+	MissingKey,
+	InvalidValue(String,ValueType),
+	//the value and the allowed values
+	NotAllowed(String,Vec<String>),
+}
+//e.g. "line 4: unknown key comand in [mc], did you mean command?"
+impl Display for SchemaError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error>{
+		if let Some(origin) = &self.origin {write!(f,"{origin}: ")?}
+		let key = self.key.as_deref().unwrap_or("");
+		let section = &self.section;
+		match &self.kind {
+			SchemaErrorKind::UnknownSection => write!(f,"unknown section [{section}]"),
+			SchemaErrorKind::MissingSection => write!(f,"missing section [{section}]"),
+			SchemaErrorKind::UnknownKey(closest) => {
+				write!(f,"unknown key {key} in [{section}]")?;
+				match closest {
+					Some(closest) => write!(f,", did you mean {closest}?"),
+					None => Ok(()),
+				}
+			},
+			SchemaErrorKind::MissingKey => write!(f,"missing key {key} in [{section}]"),
+			SchemaErrorKind::InvalidValue(value,value_type) => write!(f,"invalid value {value:?} for {key} in [{section}], expected {}",match value_type {
+				ValueType::String => "a string",
+				ValueType::Integer => "an integer",
+				ValueType::Float => "a number",
+				ValueType::Bool => "yes or no",
+			}),
+			SchemaErrorKind::NotAllowed(value,allowed) => write!(f,"invalid value {value:?} for {key} in [{section}], expected one of {}",allowed.join(", ")),
+		}
+	}
+}
+impl std::error::Error for SchemaError {}
+
+impl ConfigFile {
+	//checks every section against the schema and fills in defaults for missing keys
+	//gives back every problem found, not just the first
+	pub fn validate(&mut self, schema: &Schema) -> Result<(),Vec<SchemaError>> {
+		let mut errors = vec![];
+		for section in std::iter::once(&mut self.global).chain(self.sections.iter_mut()) {
+			match schema.find(section) {
+				Some(section_schema) => validate_section(section,section_schema,&mut errors),
+				//the global section is allowed to be empty
+				None if section.name.is_empty() => {
+					for key in section.properties.keys() {
+						errors.push(key_error(section,key,SchemaErrorKind::UnknownKey(None)));
+					}
+				},
+				None => errors.push(SchemaError {
+					section: section.name.clone(),
+					key: None,
+					origin: section.origin.clone(),
+					kind: SchemaErrorKind::UnknownSection,
+				}),
+			}
+		}
+		//====== required sections ======
+		for section_schema in &schema.sections {
+			if section_schema.required && !section_schema.pattern && self.get_section(&section_schema.name).is_none() {
+				errors.push(SchemaError {section: section_schema.name.clone(), key: None, origin: None, kind: SchemaErrorKind::MissingSection});
+			}
+		}
+		//in file order, a file at a time when some came from includes
+		errors.sort_by_key(|error| error.origin.as_ref().map_or((true,None,0),|origin| (false,origin.file.clone(),origin.line)));
+		match errors.is_empty() {
+			true => Ok(()),
+			false => Err(errors),
+		}
+	}
+}
+fn validate_section(section: &mut ConfigSection, schema: &SectionSchema, errors: &mut Vec<SchemaError>) {
+	//====== keys not in the schema ======
+	if !schema.allow_unknown_keys {
+		for key in section.properties.keys() {
+			if schema.keys.iter().any(|key_schema| key_schema.name == *key) {continue}
+			let closest = schema.keys.iter()
+				.map(|key_schema| (edit_distance(key,&key_schema.name),&key_schema.name))
+				.filter(|(distance,_)| *distance <= 2)
+				.min()
+				.map(|(_,name)| name.clone());
+			errors.push(key_error(section,key,SchemaErrorKind::UnknownKey(closest)));
+		}
+	}
+	//====== keys in the schema ======
+	for key_schema in &schema.keys {
+		let key = &key_schema.name;
+		if section.get_str(key).is_none() {
+			if key_schema.required {
+				errors.push(SchemaError {
+					section: section.name.clone(),
+					key: Some(key.clone()),
+					origin: section.origin.clone(),
+					kind: SchemaErrorKind::MissingKey,
+				});
+			}else if let Some(default) = &key_schema.default {
+				//no origin since it is not in any file
+				section.entries.push( (key.clone(),default.clone()) );
+				section.properties.insert(key.clone(),default.clone());
+			}
+			continue;
+		}
+		let values = match key_schema.list {
+			true => section.get_list(key),
+			false => section.get_str(key).into_iter().collect(),
+		};
+		let problems = values.into_iter().filter_map(|value| key_schema.check(value)).collect::<Vec<_>>();
+		for kind in problems {
+			errors.push(key_error(section,key,kind));
+		}
+	}
+}
+fn key_error(section: &ConfigSection, key: &str, kind: SchemaErrorKind) -> SchemaError {
+	SchemaError {section: section.name.clone(), key: Some(key.into()), origin: section.key_origin(key).cloned(), kind}
+}
+//* matches any number of characters
+fn glob_match(pattern: &str, name: &str) -> bool {
+	let mut parts = pattern.split('*');
+	let first = parts.next().unwrap_or("");
+	let Some(mut rest) = name.strip_prefix(first) else {return false};
+	let parts = parts.collect::<Vec<_>>();
+	let Some((last,middle)) = parts.split_last() else {return rest.is_empty()};
+	for part in middle {
+		match rest.find(part) {
+			Some(index) => rest = &rest[index + part.len()..],
+			None => return false,
+		}
+	}
+	rest.len() >= last.len() && rest.ends_with(last)
+}
+//levenshtein distance, but swapping two neighbouring characters only costs 1
+//for suggesting what was meant, args uses it for options too
+pub fn edit_distance(a: &str, b: &str) -> usize {
+	let a = a.chars().collect::<Vec<_>>();
+	let b = b.chars().collect::<Vec<_>>();
+	//distances[i][j] is the distance between a[..i] and b[..j]
+	let mut distances = vec![vec![0; b.len()+1]; a.len()+1];
+	for (i,row) in distances.iter_mut().enumerate() {row[0] = i}
+	for (j,distance) in distances[0].iter_mut().enumerate() {*distance = j}
+	for i in 1..=a.len() {
+		for j in 1..=b.len() {
+			let cost = if a[i-1] == b[j-1] {0} else {1};
+			let mut distance = (distances[i-1][j] + 1)
+				.min(distances[i][j-1] + 1)
+				.min(distances[i-1][j-1] + cost);
+			if i > 1 && j > 1 && a[i-1] == b[j-2] && a[i-2] == b[j-1] {
+				distance = distance.min(distances[i-2][j-2] + 1);
+			}
+			distances[i][j] = distance;
+		}
+	}
+	distances[a.len()][b.len()]
+}
@@ -1,4 +1,5 @@
 use tio::ThreadedIO;
+use datastructs::drop_guard::DropGuard;
 use std::process::{Command,Stdio,ExitCode};
 use std::thread;
 use std::sync::{Arc,Mutex};
@@ -25,19 +26,9 @@ impl SyncedHalt {
 		res
 	}
 	//returns a guard, that when dropped will set halt
-	fn guard(&self) -> SyncedHaltGuard<'_> {
-		SyncedHaltGuard {
-			halt: &self.halt,
-		}
-	}
-}
-//i loooooove RAII
-struct SyncedHaltGuard<'a>{
-	halt: &'a Mutex<bool>
-}
-impl Drop for SyncedHalt {
-	fn drop(&mut self){
-		*self.halt.lock().unwrap() = true;
+	//i loooooove RAII
+	fn guard(&self) -> DropGuard<&Self,fn(&Self)> {
+		DropGuard::new(self,Self::halt)
 	}
 }
 
@@ -1,5 +1,5 @@
 use exprparse::Expression;
-use args::Args;
+use args::{Parser,OptSpec,Positional,Arity,ValueType,Shell};
 use std::error::Error;
 use std::ffi::*;
 
@@ -23,27 +23,30 @@ fn gnu_readline(prompt: &str) -> Option<String> {
 }
 
 fn main() -> Result<(),Box<dyn Error>>{
-	let args = Args::new(std::env::args().collect(),vec![
-		(Some("h"), Some("help"), false),
-	])?;
-	if args.has("h","help") {
-		print_help();
+	let parser = Parser::new()
+		.positional(Positional::new("expression").arity(Arity::Optional))
+		.opt(OptSpec::new("completion").long("completion").value(ValueType::String).placeholder("shell").help("print a bash, zsh or fish completion script"))
+		.description(concat!(
+			"Providing no equation will start interactive mode\n",
+			"Interactive mode allows you to use the variable \"a\" as a substitution of the previous answer\n",
+			"and entering an empty line causes it to re run the previous expression",
+		));
+	let args = parser.parse_env()?;
+	if args.is_present("help") {
+		print!("{}",parser.help());
 		return Ok(());
 	}
-	if args.other.len() < 2 {
-		interactive_mode();
-	}else {
-		println!("{}",Expression::new(&args.other[0])?.evaluate()?);
+	if let Some(shell) = args.get::<Shell>("completion")? {
+		print!("{}",parser.completion(shell));
+		return Ok(());
+	}
+	match args.get_str("expression") {
+		None => interactive_mode(),
+		Some(expression) => println!("{}",Expression::new(expression)?.evaluate()?),
 	}
 	Ok(())
 }
 
-fn print_help(){
-	println!("Usage: {} [options] [expression]",std::env::args().next().unwrap());
-	println!("Providing no equation will start interactive mode");
-	println!("Interactive mode allows you to use the variable \"a\" as a substitution of the previous answer\nand entering an empty line causes it to re run the previous expression");
-}
-
 fn interactive_mode(){
 	use std::collections::HashMap;
 	let mut vars = HashMap::from([("a".to_string(),0.0)]);
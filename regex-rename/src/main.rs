@@ -1,18 +1,31 @@
-use std::env;
 use std::io::Error;
 use std::fs::rename;
 use std::path::Path;
 use std::process;
 use regex;
+use args::{Parser,Positional,ValueType};
 fn main() -> Result<(),regex::Error>{
-	let args = env::args().collect::<Vec<_>>();
-	if args.len() != 4 {
-		print_help();
-		process::exit(1);
-	}
-	let replacement_name = &args[3];
-	let regex_expr = &args[2];
-	let file_path = &args[1];
+	let parser = Parser::new()
+		.description("designed to be used in conjunction with the find command")
+		.positional(Positional::new("filename").value(ValueType::Path))
+		.positional(Positional::new("match").help("the regex to find and replace with `replacement`"))
+		.positional(Positional::new("replacement"));
+	let args = match parser.parse_env(){
+		Ok(args) if args.is_present("help") => {
+			print!("{}",parser.help());
+			process::exit(0);
+		},
+		Ok(args) => args,
+		Err(e) => {
+			eprintln!("{e}");
+			eprintln!("{}",parser.usage_line());
+			process::exit(1);
+		}
+	};
+	//all three are required so they are always there
+	let replacement_name = args.get_str("replacement").unwrap();
+	let regex_expr = args.get_str("match").unwrap();
+	let file_path = args.get_str("filename").unwrap();
 	//====== compile regex ======
 	let regex = regex::Regex::new(regex_expr)?;
 	//====== check the file exists ======
@@ -40,9 +53,3 @@ fn main() -> Result<(),regex::Error>{
 	//return
 	Ok(())
 }
-fn print_help(){
-	let name = std::env::args().next().unwrap();
-	println!("designed to be used in conjunction with the find command");
-	println!("Usage: {name} [options] <filename> <match> <replacement>");
-	println!("	where `match` is the regex to find and replace with `replacement`");
-}
@@ -1,9 +1,7 @@
 use std::path::Path;
 use std::os::unix::fs::MetadataExt;
 use std::ffi::OsString;
-use std::env;
-use args::Args;
-use args::ArgError::*;
+use args::{Parser,OptSpec,Positional,Arity,ValueType,Hint,Shell};
 
 #[link(name = "term")]
 unsafe extern "C" {
@@ -15,49 +13,53 @@ struct Config {
 	max_depth: usize,
 }
 
-fn print_help(){
-	println!("usage: {} [options] [path] ...",env::args().next().unwrap());
-	println!("options:");
-	println!("	-h, --help      : print help");
-	println!("	-n, --no-colour : turn off colour");
-	println!("	-d, --depth <n> : the maximum depth to look through");
-}
 fn main() {
 	let mut config = Config {
 		colour: true,
 		max_depth: usize::MAX,
 	};
 	//====== parse arguments ======
-	let format = vec![
-	//       short      long               parameter
-		(Some("h"), Some("help"),      false    ),
-		(Some("n"), Some("no-colour"), false    ),
-		(Some("d"), Some("depth"),     true     ),
-	];
-	let args = match Args::new(env::args().collect(),format){
+	let parser = Parser::new()
+		.positional(Positional::new("path").arity(Arity::ZeroOrMore).hint(Hint::Directory))
+		.opt(OptSpec::new("help").short("h").long("help").help("print help"))
+		.opt(OptSpec::new("no-colour").short("n").long("no-colour").help("turn off colour"))
+		.opt(OptSpec::new("depth").short("d").long("depth").value(ValueType::Integer).help("the maximum depth to look through"))
+		.opt(OptSpec::new("completion").long("completion").value(ValueType::String).placeholder("shell").help("print a bash, zsh or fish completion script"));
+	let args = match parser.parse_env(){
 		Ok(args) => args,
-		Err(e) => match e {
-			UnknownArgument(t) => {
-				eprintln!("Error: unknown argument {:?}",t);
-				return;
-			},
-			MissingParameter(t) => {
-				eprintln!("Error: missing parameter to {:?}",t);
-				return;
-			}
+		Err(e) => {
+			eprintln!("Error: {e}");
+			return;
 		}
 	};
-	if args.has_short("h") || args.has_long("help") {
-		print_help();
+	if args.is_present("help") {
+		print!("{}",parser.help());
 		return;
 	}
-	if args.has_short("n") || args.has_long("no-colour") {config.colour = false}
-	if let Some(depth_arg) = args.get_arg(Some("d"),Some("depth")) {config.max_depth = depth_arg.parse().unwrap_or(usize::MAX)}
+	match args.get::<Shell>("completion") {
+		Ok(Some(shell)) => {
+			print!("{}",parser.completion(shell));
+			return;
+		},
+		Ok(None) => (),
+		Err(e) => {
+			eprintln!("Error: {e}");
+			return;
+		},
+	}
+	if args.is_present("no-colour") {config.colour = false}
+	match args.get::<usize>("depth") {
+		Ok(depth) => config.max_depth = depth.unwrap_or(usize::MAX),
+		Err(e) => {
+			eprintln!("Error: {e}");
+			return;
+		},
+	}
 	//====== print_dir for each path given ======
-	let mut dirs = args.other[1..].to_vec(); //exclude argv[0]
-	if dirs.len() == 0 {dirs.push(".".into())}
+	let mut dirs = args.get_all("path");
+	if dirs.is_empty() {dirs.push(".")}
 	for arg in dirs{
-		let dir = Path::new(&arg);
+		let dir = Path::new(arg);
 		let name = if config.colour {get_pretty_name(dir)} else {get_path_name(dir)};
 		println!("{}",name);
 		if dir.exists() {print_dir(dir,"".into(),0,&config)}
@@ -5,6 +5,7 @@ use termios::*;
 use std::os::fd::{AsFd,AsRawFd};
 use std::cell::RefCell;
 use std::sync::Mutex;
+use datastructs::drop_guard::DropGuard;
 
 struct InputHistory {
 	buffer: Vec<Vec<char>>,
@@ -24,7 +25,8 @@ pub struct ThreadedIO {
 	io_lock: Mutex<()>,
 	//input_buffer: Mutex<RefCell<Vec<char>>>,
 	current_prompt_state: Mutex<RefCell<String>>,
-	old_term_settings: Termios,
+	//puts the terminal back when dropped
+	old_term_settings: DropGuard<Termios,fn(Termios)>,
 	interupt: Mutex<bool>,
 	history: Mutex<InputHistory>,
 	pub handle_history: bool,
@@ -51,7 +53,7 @@ impl ThreadedIO {
 			io_lock: Mutex::new(()),
 			//input_buffer: Mutex::new(RefCell::new(vec![])),
 			current_prompt_state: Mutex::new(RefCell::new("".to_string())),
-			old_term_settings: Termios::from_fd(io::stdin().as_raw_fd()).unwrap(),
+			old_term_settings: DropGuard::new(Termios::from_fd(io::stdin().as_raw_fd()).unwrap(),restore_term),
 			interupt: Mutex::new(false),
 			history: Mutex::new(InputHistory::new()),
 			handle_history: false,
@@ -179,7 +181,7 @@ impl ThreadedIO {
 		*lock = true;
 	}
 	pub fn reset_term(&self){
-		tcsetattr(io::stdin().as_raw_fd(),TCSANOW,&self.old_term_settings).unwrap();
+		restore_term(*self.old_term_settings);
 	}
 	fn get_escape_sequence(&self) -> io::Result<EscapeCode>{
 		let getch = || io::stdin()
@@ -246,8 +248,6 @@ impl ThreadedIO {
 		history.buffer[current_index].clone()
 	}
 }
-impl Drop for ThreadedIO{
-	fn drop(&mut self){
-		self.reset_term();
-	}
+fn restore_term(settings: Termios){
+	tcsetattr(io::stdin().as_raw_fd(),TCSANOW,&settings).unwrap();
 }
@@ -1,78 +1,99 @@
 #![feature(trim_prefix_suffix)]
-use args::Args;
-use iniconfig::ConfigFile;
-use std::fs;
+use args::{Parser,OptSpec,ValueType,Shell};
+use iniconfig::{ConfigFile,DuplicateSections,ParseOptions,Schema,SectionSchema,KeySchema};
 use std::env;
-use std::path::Path;
+use std::path::PathBuf;
 use std::process::{Command,ExitCode};
 
-fn print_help(){
-	println!("usage: {} [options]",env::args().next().unwrap_or("program".to_string()));
-	println!("	-h, --help          : print help");
-	println!("	-c, --config <path> : use config file at path provided");
-	println!("The default config file is in ~/.config/tmux-service-manager/config.ini");
-	println!("Example:");
-	println!("[session name]");
-	println!("cwd=current/working/directory");
-	println!("command=custom --command -to run");
-	println!("#comment");
-	println!("[Other session name]");
-	println!("#sessions may be empty to run");
-	println!("#a default tmux session");
-	println!("#cwd and command are optional");
-}
+const EPILOG: &str = "\
+The default config file is in ~/.config/tmux-service-manager/config.ini
+Example:
+[session name]
+cwd=current/working/directory
+command=custom --command -to run
+#comment
+[Other session name]
+#sessions may be empty to run
+#a default tmux session
+#cwd and command are optional";
 
 fn main() -> ExitCode {
-	let config_path;
 	//====== read command line ======
-	//skip argv[0]
-	let cmd_line = env::args()
-		.enumerate()
-		.filter(|(i,_)| *i != 0)
-		.map(|(_,x)| x)
-		.collect::<Vec<_>>();
-	let options = vec![
-		(Some("h"),Some("help"),false),
-		(Some("c"),Some("config"),true),
-	];
-	let args = match Args::new(cmd_line,options){
+	let parser = Parser::new()
+		.opt(OptSpec::new("help").short("h").long("help").help("print help"))
+		.opt(OptSpec::new("config").short("c").long("config").value(ValueType::Path).help("use config file at path provided"))
+		.opt(OptSpec::new("interpolate").short("i").long("interpolate").help("expand ${key}, ${section:key} and ${env:VAR} in the config"))
+		.opt(OptSpec::new("completion").long("completion").value(ValueType::String).placeholder("shell").help("print a bash, zsh or fish completion script"))
+		.epilog(EPILOG);
+	let args = match parser.parse_env(){
 		Ok(args) => args,
 		Err(e) => {
-			eprintln!("FATAL: Error parsing arguments: {:?}",e);
+			eprintln!("FATAL: Error parsing arguments: {e}");
 			return ExitCode::FAILURE
 		}
 	};
 	//====== apply command line options ======
-	if args.has("h","help"){
-		print_help();
+	if args.is_present("help"){
+		print!("{}",parser.help());
 		return ExitCode::SUCCESS
 	}
-	if args.has("c","config"){
-		config_path = Path::new(args.get_arg(Some("c"),Some("config")).unwrap());
-	}else {
-		let mut default_config_path = match env::home_dir(){
-			Some(dir) => dir,
+	match args.get::<Shell>("completion"){
+		Ok(Some(shell)) => {
+			print!("{}",parser.completion(shell));
+			return ExitCode::SUCCESS
+		},
+		Ok(None) => (),
+		Err(e) => {
+			eprintln!("FATAL: {e}");
+			return ExitCode::FAILURE
+		}
+	}
+	let config_path = match args.get::<PathBuf>("config"){
+		Ok(Some(path)) => path,
+		Ok(None) => match env::home_dir(){
+			Some(dir) => dir.join(".config/tmux-service-manager/config.ini"),
 			None => {
 				eprintln!("FATAL: Cannot determine home directory.");
 				return ExitCode::FAILURE
 			}
-		};
-		default_config_path.push(".config/tmux-service-manager/config.ini");
-		config_path = default_config_path.leak();
-	}
+		},
+		Err(e) => {
+			eprintln!("FATAL: {e}");
+			return ExitCode::FAILURE
+		}
+	};
 	//====== read config file ======
 	if !config_path.exists(){
 		eprintln!("FATAL: Config file does not exist.");
 		return ExitCode::FAILURE
 	}
-	let config_file = match fs::read_to_string(config_path){
-		Ok(file) => file,
+	//includes let a per host config pull in a shared one
+	//lenient so files that worked before still do, odd lines are skipped like ConfigFile::from
+	let options = ParseOptions::new().lenient().duplicate_sections(DuplicateSections::KeepAll);
+	let mut config = match ConfigFile::load_with(&config_path,&options){
+		Ok(config) => config,
 		Err(e) => {
-			eprintln!("FATAL: error while reading config: {:?}",e);
+			eprintln!("FATAL: error in config file {e}");
 			return ExitCode::FAILURE
 		}
 	};
-	let config = ConfigFile::from(config_file.as_str());
+	//catches typos like comand= instead of ignoring them
+	let schema = Schema::new()
+		.section(SectionSchema::matching("*")
+			.key(KeySchema::new("command"))
+			.key(KeySchema::new("cwd")));
+	if let Err(errors) = config.validate(&schema){
+		for e in errors {
+			eprintln!("FATAL: error in config file {e}");
+		}
+		return ExitCode::FAILURE
+	}
+	//e.g. cwd=${env:HOME}/servers/mc
+	//only when asked, commands are shell and often have their own ${VAR}s
+	if args.is_present("interpolate") && let Err(e) = config.interpolate(){
+		eprintln!("FATAL: error in config file {e}");
+		return ExitCode::FAILURE
+	}
 	//println!("{:?}",config);
 	//====== start tmuxes ======
 	for section in config {